@@ -0,0 +1,58 @@
+#![feature(test)]
+extern crate test;
+extern crate chomp;
+
+use test::Bencher;
+
+use chomp::*;
+use chomp::buffer::{Buffer, FixedSizeBuffer, GrowingBuffer, RingBuffer, Source, Stream, StreamError};
+
+const RECORD: &[u8] = b"2016-01-24 12:00:00 INFO small record\n";
+
+fn data() -> Vec<u8> {
+    RECORD.iter().cloned().cycle().take(RECORD.len() * 16 * 1024).collect()
+}
+
+fn record<'a>(i: Input<'a, u8>) -> U8Result<'a, &'a [u8]> {
+    take_till(i, |c| c == b'\n').bind(|i, l| token(i, b'\n').map(|_| l))
+}
+
+fn parse_records<B: Buffer<u8>>(data: &[u8], buffer: B) -> usize {
+    let mut s = Source::from_read(data, buffer);
+    let mut n = 0;
+
+    loop {
+        match s.parse(record) {
+            Ok(_)                        => n += 1,
+            Err(StreamError::Retry)      => {},
+            Err(StreamError::EndOfInput) => break,
+            Err(e)                       => panic!("{:?}", e),
+        }
+    }
+
+    n
+}
+
+#[bench]
+fn records_fixed_size(b: &mut Bencher) {
+    let data = data();
+
+    b.bytes = data.len() as u64;
+    b.iter(|| parse_records(&data, FixedSizeBuffer::with_size(64 * 1024)))
+}
+
+#[bench]
+fn records_growing(b: &mut Bencher) {
+    let data = data();
+
+    b.bytes = data.len() as u64;
+    b.iter(|| parse_records(&data, GrowingBuffer::with_limit(64 * 1024)))
+}
+
+#[bench]
+fn records_ring(b: &mut Bencher) {
+    let data = data();
+
+    b.bytes = data.len() as u64;
+    b.iter(|| parse_records(&data, RingBuffer::with_size(64 * 1024).unwrap()))
+}
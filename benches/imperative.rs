@@ -0,0 +1,138 @@
+//! Imperative counterparts of the parsers in `combinators.rs`, the two should perform the same.
+#![feature(test)]
+extern crate test;
+extern crate chomp;
+
+use test::Bencher;
+
+use std::iter;
+
+use chomp::*;
+use chomp::buffer::{Stream, IntoStream};
+use chomp::imperative::{self, lift, opt, run};
+
+#[bench]
+fn count_vec_1k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(1024).collect::<Vec<u8>>();
+
+    fn count_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = Vec::with_capacity(1024);
+
+            for _ in 0..1024 {
+                v.push(run(i, any)?);
+            }
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(count_vec)
+    })
+}
+
+#[bench]
+fn count_vec_10k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(10024).collect::<Vec<u8>>();
+
+    fn count_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = Vec::with_capacity(10024);
+
+            for _ in 0..10024 {
+                v.push(run(i, any)?);
+            }
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(count_vec)
+    })
+}
+
+fn many_any<'a, I: Copy>(i: &mut Input<'a, I>, v: &mut Vec<I>) -> imperative::Result<(), Error<I>> {
+    while let Some(c) = opt(i, |i| run(i, any))? {
+        v.push(c);
+    }
+
+    Ok(())
+}
+
+#[bench]
+fn many_vec_1k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(1024).collect::<Vec<u8>>();
+
+    fn many_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = Vec::new();
+
+            many_any(i, &mut v)?;
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(many_vec)
+    })
+}
+
+#[bench]
+fn many_vec_10k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(10024).collect::<Vec<u8>>();
+
+    fn many_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = Vec::new();
+
+            many_any(i, &mut v)?;
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(many_vec)
+    })
+}
+
+#[bench]
+fn many1_vec_1k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(1024).collect::<Vec<u8>>();
+
+    fn many1_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = vec![run(i, any)?];
+
+            many_any(i, &mut v)?;
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(many1_vec)
+    })
+}
+
+#[bench]
+fn many1_vec_10k(b: &mut Bencher) {
+    let data = iter::repeat(b'a').take(10024).collect::<Vec<u8>>();
+
+    fn many1_vec<I: Copy>(i: Input<I>) -> ParseResult<I, Vec<I>, Error<I>> {
+        lift(i, |i| {
+            let mut v = vec![run(i, any)?];
+
+            many_any(i, &mut v)?;
+
+            Ok(v)
+        })
+    }
+
+    b.iter(|| {
+        data.into_stream().parse(many1_vec)
+    })
+}
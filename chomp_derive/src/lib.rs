@@ -0,0 +1,389 @@
+//! Derive macro generating chomp parsers for structs and enums.
+//!
+//! `#[derive(Parse)]` implements an inherent `parse` function for the type, parsing the fields in
+//! declaration order:
+//!
+//! ```ignore
+//! pub fn parse(i: Input<u8>) -> U8Result<Self>
+//! ```
+//!
+//! How each field is parsed is controlled using `#[chomp(...)]` attributes:
+//!
+//! * `#[chomp(be_u32)]`: Parses the field using the parser function at the given path, which has
+//!   to be in scope.
+//! * `#[chomp(token = b',')]`: Matches the given token, the field is of type `u8`.
+//! * `#[chomp(count = "len")]`: Parses the number of items given by the expression, which can
+//!   refer to fields declared before it, into a collection. The items are parsed using the parser
+//!   at the given path if there is one (eg. `#[chomp(count = "len", be_u16)]`), otherwise using
+//!   `parse` of the item type of a `Vec<T>` field.
+//! * No attribute: The field is parsed using the `parse` function of its type, usually from
+//!   another `#[derive(Parse)]`.
+//!
+//! Enum variants are selected by a tag preceding the fields of the variant, declared using
+//! `#[chomp(tag = ...)]` on each variant. The tag is parsed using the parser at the path given in
+//! `#[chomp(...)]` on the enum, or `any` if none is given, and compared to the tag of each variant.
+//! If all tags are byte strings and the enum does not declare a parser, the tags are instead
+//! matched using `string` and the variants are attempted in declaration order.
+//!
+//! The type can have at most one lifetime parameter, which is then used as the lifetime of the
+//! input. This allows fields to borrow from the input.
+//!
+//! ```
+//! #[macro_use] extern crate chomp_derive;
+//! extern crate chomp;
+//!
+//! use chomp::{Input, U8Result, parse_only, take};
+//!
+//! fn be_u16(i: Input<u8>) -> U8Result<u16> {
+//!     take(i, 2).map(|b| (b[0] as u16) << 8 | b[1] as u16)
+//! }
+//!
+//! #[derive(Debug, PartialEq, Parse)]
+//! struct Point {
+//!     #[chomp(be_u16)]
+//!     x: u16,
+//!     #[chomp(be_u16)]
+//!     y: u16,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Parse)]
+//! enum Shape {
+//!     #[chomp(tag = 1)]
+//!     Dot(Point),
+//!     #[chomp(tag = 2)]
+//!     Line {
+//!         #[chomp(be_u16)]
+//!         len:    u16,
+//!         #[chomp(count = "len")]
+//!         points: Vec<Point>,
+//!     },
+//! }
+//!
+//! # fn main() {
+//! assert_eq!(parse_only(Shape::parse, b"\x01\x00\x01\x00\x02"), Ok(Shape::Dot(Point { x: 1, y: 2 })));
+//! assert_eq!(parse_only(Shape::parse, b"\x02\x00\x01\x00\x03\x00\x04"),
+//!            Ok(Shape::Line { len: 1, points: vec![Point { x: 3, y: 4 }] }));
+//! # }
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Attribute, Data, DeriveInput, Expr, ExprLit, Fields, GenericArgument, Ident, Lit, LitStr,
+          Path, PathArguments, Result, Type};
+use syn::spanned::Spanned;
+
+/// Derives an inherent `parse` function, see the crate documentation.
+#[proc_macro_derive(Parse, attributes(chomp))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as DeriveInput);
+
+    match expand(&ast) {
+        Ok(t)  => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(ast: &DeriveInput) -> Result<TokenStream2> {
+    if let Some(p) = ast.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(p, "Parse cannot be derived for types with type parameters"));
+    }
+
+    if let Some(p) = ast.generics.const_params().next() {
+        return Err(syn::Error::new_spanned(p, "Parse cannot be derived for types with const parameters"));
+    }
+
+    let mut lifetimes = ast.generics.lifetimes();
+    let lifetime      = lifetimes.next().map(|l| l.lifetime.clone());
+
+    if let Some(l) = lifetimes.next() {
+        return Err(syn::Error::new_spanned(l, "Parse cannot be derived for types with more than one lifetime"));
+    }
+
+    let name = &ast.ident;
+    let vis  = &ast.vis;
+    let body = match ast.data {
+        Data::Struct(ref s) => {
+            no_attrs(&ast.attrs)?;
+
+            fields(&s.fields, quote!{ #name })?
+        },
+        Data::Enum(ref e)   => {
+            let tag_parser = enum_attrs(&ast.attrs)?;
+            let mut tags   = Vec::with_capacity(e.variants.len());
+            let mut bodies = Vec::with_capacity(e.variants.len());
+
+            for v in &e.variants {
+                let ident = &v.ident;
+
+                tags.push(variant_attrs(&v.attrs)?.ok_or_else(|| syn::Error::new_spanned(v, "missing #[chomp(tag = ...)] on enum variant"))?);
+                bodies.push(fields(&v.fields, quote!{ #name::#ident })?);
+            }
+
+            variants(tag_parser, &tags, &bodies, ast.span())?
+        },
+        Data::Union(ref u)  => return Err(syn::Error::new_spanned(u.union_token, "Parse cannot be derived for unions")),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let i               = input();
+    let (input, result) = match lifetime {
+        Some(l) => (quote!{ ::chomp::Input<#l, u8> }, quote!{ ::chomp::U8Result<#l, Self> }),
+        None    => (quote!{ ::chomp::Input<u8> },     quote!{ ::chomp::U8Result<Self> }),
+    };
+
+    Ok(quote!{
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Parses the type field by field, generated by `#[derive(Parse)]`.
+            #[allow(dead_code)]
+            #vis fn parse(#i: #input) -> #result {
+                #body
+            }
+        }
+    })
+}
+
+/// Parser for the fields of a struct or variant, constructing it using ``ctor``.
+fn fields(fields: &Fields, ctor: TokenStream2) -> Result<TokenStream2> {
+    let mut names   = Vec::with_capacity(fields.len());
+    let mut types   = Vec::with_capacity(fields.len());
+    let mut parsers = Vec::with_capacity(fields.len());
+
+    for (n, f) in fields.iter().enumerate() {
+        names.push(f.ident.clone().unwrap_or_else(|| Ident::new(&format!("__field{}", n), Span::call_site())));
+        types.push(&f.ty);
+        parsers.push(field(f.ty.clone(), FieldAttrs::parse(&f.attrs)?)?);
+    }
+
+    let i     = input();
+    let value = match *fields {
+        Fields::Named(_)   => quote!{ #ctor { #(#names),* } },
+        Fields::Unnamed(_) => quote!{ #ctor ( #(#names),* ) },
+        Fields::Unit       => ctor,
+    };
+
+    // The types are spelled out so the fields can be used in the count of later fields
+    Ok(names.iter().zip(types).zip(parsers).rev().fold(quote!{ #i.ret(#value) }, |body, ((name, ty), p)| quote!{
+        #p.bind(|#i, #name: #ty| #body)
+    }))
+}
+
+/// Parser for the enum variants with the given tags and bodies.
+fn variants(tag_parser: Option<Path>, tags: &[Expr], bodies: &[TokenStream2], span: Span) -> Result<TokenStream2> {
+    if bodies.is_empty() {
+        return Err(syn::Error::new(span, "Parse cannot be derived for enums without variants"));
+    }
+
+    let i       = input();
+    let strings = tags.iter().filter(|t| is_byte_str(t)).count();
+
+    if tag_parser.is_none() && strings == tags.len() {
+        let last        = tags.len() - 1;
+        let (tag, body) = (&tags[last], &bodies[last]);
+
+        return Ok(tags[..last].iter().zip(&bodies[..last]).rev().fold(
+            quote!{ ::chomp::parsers::string(#i, #tag).then(|#i| #body) },
+            |rest, (tag, body)| quote!{
+                ::chomp::combinators::or(#i, |#i| ::chomp::parsers::string(#i, #tag).then(|#i| #body), |#i| #rest)
+            }));
+    }
+
+    if tag_parser.is_none() && strings > 0 {
+        return Err(syn::Error::new(span, "byte string tags require all tags to be byte strings or a tag parser declared using #[chomp(...)] on the enum"));
+    }
+
+    let tag_parser = tag_parser.map(|p| quote!{ #p }).unwrap_or_else(|| quote!{ ::chomp::parsers::any });
+    let indices    = (0..tags.len()).collect::<Vec<_>>();
+    let tag        = Ident::new("__chomp_tag", Span::mixed_site());
+    let n          = Ident::new("__chomp_variant", Span::mixed_site());
+
+    Ok(quote!{
+        ::chomp::combinators::filter(#i, #tag_parser, |#tag| #(if #tag == #tags { Some(#indices) } else)* { None })
+            .bind(|#i, #n: usize| match #n {
+                #(#indices => #bodies,)*
+                _ => unreachable!(),
+            })
+    })
+}
+
+/// Parser for a single field.
+fn field(ty: Type, attrs: FieldAttrs) -> Result<TokenStream2> {
+    let i = input();
+
+    if let Some(t) = attrs.token {
+        return Ok(quote!{ ::chomp::parsers::token(#i, #t) });
+    }
+
+    let item = match attrs.parser {
+        Some(p) => quote!{ #p },
+        None    => match attrs.count {
+            Some(_) => {
+                let t = item_type(&ty).ok_or_else(|| syn::Error::new_spanned(&ty, "#[chomp(count = ...)] requires either a parser for the items or a Vec<T> field"))?;
+
+                quote!{ <#t>::parse }
+            },
+            None    => quote!{ <#ty>::parse },
+        },
+    };
+
+    Ok(match attrs.count {
+        Some(n) => quote!{ ::chomp::combinators::count(#i, (#n) as usize, #item) },
+        None    => quote!{ #item(#i) },
+    })
+}
+
+/// The input of the generated parser, hygienic so it cannot collide with the names of fields.
+fn input() -> Ident {
+    Ident::new("__chomp_input", Span::mixed_site())
+}
+
+/// The ``T`` of a ``Vec<T>``.
+fn item_type(ty: &Type) -> Option<&Type> {
+    let p = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => p.path.segments.last()?,
+        _                                      => return None,
+    };
+
+    match p.arguments {
+        PathArguments::AngleBracketed(ref a) if p.ident == "Vec" && a.args.len() == 1 => match a.args[0] {
+            GenericArgument::Type(ref t) => Some(t),
+            _                            => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_byte_str(e: &Expr) -> bool {
+    matches!(*e, Expr::Lit(ExprLit { lit: Lit::ByteStr(_), .. }))
+}
+
+/// The ways a field can be parsed, from its `#[chomp(...)]` attributes.
+#[derive(Default)]
+struct FieldAttrs {
+    parser: Option<Path>,
+    token:  Option<Expr>,
+    count:  Option<Expr>,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut r = FieldAttrs::default();
+
+        for a in attrs.iter().filter(|a| a.path().is_ident("chomp")) {
+            a.parse_nested_meta(|meta| {
+                if meta.path.is_ident("token") {
+                    set(&mut r.token, meta.value()?.parse()?, &meta.path)
+                } else if meta.path.is_ident("count") {
+                    let s: LitStr = meta.value()?.parse()?;
+
+                    set(&mut r.count, s.parse()?, &meta.path)
+                } else if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+                    set(&mut r.parser, meta.path.clone(), &meta.path)
+                } else {
+                    Err(meta.error("unknown chomp field attribute"))
+                }
+            })?;
+
+            if r.token.is_some() && (r.parser.is_some() || r.count.is_some()) {
+                return Err(syn::Error::new_spanned(a, "#[chomp(token = ...)] cannot be combined with other parsers"));
+            }
+        }
+
+        Ok(r)
+    }
+}
+
+/// The tag parser of an enum.
+fn enum_attrs(attrs: &[Attribute]) -> Result<Option<Path>> {
+    let mut r = None;
+
+    for a in attrs.iter().filter(|a| a.path().is_ident("chomp")) {
+        a.parse_nested_meta(|meta| {
+            if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+                set(&mut r, meta.path.clone(), &meta.path)
+            } else {
+                Err(meta.error("expected the path to a parser for the tag of the enum"))
+            }
+        })?;
+    }
+
+    Ok(r)
+}
+
+/// The tag of an enum variant.
+fn variant_attrs(attrs: &[Attribute]) -> Result<Option<Expr>> {
+    let mut r = None;
+
+    for a in attrs.iter().filter(|a| a.path().is_ident("chomp")) {
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                set(&mut r, meta.value()?.parse()?, &meta.path)
+            } else {
+                Err(meta.error("unknown chomp variant attribute, expected tag"))
+            }
+        })?;
+    }
+
+    Ok(r)
+}
+
+fn no_attrs(attrs: &[Attribute]) -> Result<()> {
+    match attrs.iter().find(|a| a.path().is_ident("chomp")) {
+        Some(a) => Err(syn::Error::new_spanned(a, "#[chomp(...)] is only allowed on fields of structs")),
+        None    => Ok(()),
+    }
+}
+
+fn set<T>(slot: &mut Option<T>, value: T, path: &Path) -> Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(path, "duplicate chomp attribute"));
+    }
+
+    *slot = Some(value);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::expand;
+
+    fn error(src: &str) -> String {
+        match expand(&syn::parse_str(src).unwrap()) {
+            Ok(t)  => panic!("expected an error, got: {}", t),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn unsupported_types() {
+        assert_eq!(error("struct A<T>(T);"), "Parse cannot be derived for types with type parameters");
+        assert_eq!(error("struct A<const N: usize>;"), "Parse cannot be derived for types with const parameters");
+        assert_eq!(error("struct A<'a, 'b>(&'a [u8], &'b [u8]);"), "Parse cannot be derived for types with more than one lifetime");
+        assert_eq!(error("union A { a: u8 }"), "Parse cannot be derived for unions");
+        assert_eq!(error("enum A {}"), "Parse cannot be derived for enums without variants");
+    }
+
+    #[test]
+    fn field_attributes() {
+        assert_eq!(error("struct A { #[chomp(foo = 1)] a: u8 }"), "unknown chomp field attribute");
+        assert_eq!(error("struct A { #[chomp(any, any)] a: u8 }"), "duplicate chomp attribute");
+        assert_eq!(error("struct A { #[chomp(token = b'a', any)] a: u8 }"), "#[chomp(token = ...)] cannot be combined with other parsers");
+        assert_eq!(error("struct A { #[chomp(count = \"2\")] a: [u8; 2] }"), "#[chomp(count = ...)] requires either a parser for the items or a Vec<T> field");
+        assert_eq!(error("#[chomp(any)] struct A;"), "#[chomp(...)] is only allowed on fields of structs");
+    }
+
+    #[test]
+    fn enum_attributes() {
+        assert_eq!(error("enum A { B }"), "missing #[chomp(tag = ...)] on enum variant");
+        assert_eq!(error("enum A { #[chomp(foo = 1)] B }"), "unknown chomp variant attribute, expected tag");
+        assert_eq!(error("#[chomp(tag = 1)] enum A { #[chomp(tag = 1)] B }"), "expected the path to a parser for the tag of the enum");
+        assert_eq!(error("enum A { #[chomp(tag = b\"a\")] B, #[chomp(tag = 1)] C }"),
+                   "byte string tags require all tags to be byte strings or a tag parser declared using #[chomp(...)] on the enum");
+    }
+}
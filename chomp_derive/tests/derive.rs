@@ -0,0 +1,210 @@
+#[macro_use]
+extern crate chomp_derive;
+extern crate chomp;
+
+use chomp::{Input, U8Result, ParseError, parse_only, take, take_while1};
+use chomp::ascii::decimal;
+
+fn be_u16(i: Input<u8>) -> U8Result<u16> {
+    take(i, 2).map(|b| (b[0] as u16) << 8 | b[1] as u16)
+}
+
+fn word<'a>(i: Input<'a, u8>) -> U8Result<'a, &'a [u8]> {
+    take_while1(i, |c| c != b',' && c != b';')
+}
+
+#[derive(Debug, PartialEq, Parse)]
+struct Header {
+    #[chomp(be_u16)]
+    kind: u16,
+    #[chomp(be_u16)]
+    len:  u16,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+struct Record {
+    header: Header,
+    #[chomp(count = "header.len", be_u16)]
+    data:   Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+struct Row<'a> {
+    #[chomp(word)]
+    name:  &'a [u8],
+    #[chomp(token = b',')]
+    _sep:  u8,
+    #[chomp(decimal)]
+    value: u32,
+    #[chomp(token = b';')]
+    _end:  u8,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+struct Pair(Header, #[chomp(be_u16)] u16);
+
+#[derive(Debug, PartialEq, Parse)]
+struct Nothing;
+
+#[derive(Debug, PartialEq, Parse)]
+struct Table<'a> {
+    #[chomp(decimal)]
+    rows:  usize,
+    #[chomp(token = b':')]
+    _sep:  u8,
+    #[chomp(count = "rows")]
+    items: Vec<Row<'a>>,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+enum Message {
+    #[chomp(tag = 1)]
+    Ping,
+    #[chomp(tag = 2)]
+    Data(Record),
+    #[chomp(tag = b'h')]
+    Pair {
+        #[chomp(be_u16)]
+        a: u16,
+        #[chomp(be_u16)]
+        b: u16,
+    },
+}
+
+#[derive(Debug, PartialEq, Parse)]
+#[chomp(be_u16)]
+enum Wide {
+    #[chomp(tag = 0x0102)]
+    A,
+    #[chomp(tag = 0x0304)]
+    B(#[chomp(be_u16)] u16),
+}
+
+// Fields named like the bindings used by the generated parser
+#[derive(Debug, PartialEq, Parse)]
+struct Names {
+    #[chomp(be_u16)]
+    i: u16,
+    #[chomp(be_u16)]
+    n: u16,
+    #[chomp(count = "i + n", be_u16)]
+    t: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+enum Tagged {
+    #[chomp(tag = 1)]
+    A {
+        #[chomp(be_u16)]
+        n: u16,
+        #[chomp(be_u16)]
+        i: u16,
+    },
+    #[chomp(tag = 2)]
+    B(Names),
+}
+
+const KIND_EMPTY: u16 = 0x0a00;
+const KIND_LIST:  u16 = 0x0b00;
+
+#[derive(Debug, PartialEq, Parse)]
+#[chomp(self::be_u16)]
+enum Kind {
+    #[chomp(tag = KIND_EMPTY)]
+    Empty,
+    #[chomp(tag = KIND_LIST)]
+    List {
+        #[chomp(decimal)]
+        len:   u8,
+        #[chomp(token = b':')]
+        _sep:  u8,
+        #[chomp(count = "len * 2")]
+        items: Vec<Header>,
+    },
+}
+
+#[derive(Debug, PartialEq, Parse)]
+struct Counted {
+    #[chomp(be_u16)]
+    len:     u16,
+    #[chomp(count = "0", be_u16)]
+    none:    Vec<u16>,
+    #[chomp(count = "len", self::be_u16)]
+    values:  Vec<u16>,
+    #[chomp(count = "values.len() - 1")]
+    headers: Vec<Header>,
+}
+
+#[derive(Debug, PartialEq, Parse)]
+enum Method {
+    #[chomp(tag = b"GET")]
+    Get,
+    #[chomp(tag = b"POST ")]
+    Post(#[chomp(decimal)] u32),
+}
+
+#[test]
+fn structs() {
+    assert_eq!(parse_only(Header::parse, b"\x00\x01\x00\x02"), Ok(Header { kind: 1, len: 2 }));
+    assert_eq!(parse_only(Record::parse, b"\x00\x01\x00\x02\x00\x03\x00\x04"),
+               Ok(Record { header: Header { kind: 1, len: 2 }, data: vec![3, 4] }));
+    assert_eq!(parse_only(Row::parse, b"foo,123;"), Ok(Row { name: b"foo", _sep: b',', value: 123, _end: b';' }));
+    assert_eq!(parse_only(Pair::parse, b"\x00\x01\x00\x00\x00\x05"), Ok(Pair(Header { kind: 1, len: 0 }, 5)));
+    assert_eq!(parse_only(Nothing::parse, b""), Ok(Nothing));
+    assert_eq!(parse_only(Table::parse, b"2:a,1;b,2;"), Ok(Table {
+        rows:  2,
+        _sep:  b':',
+        items: vec![Row { name: b"a", _sep: b',', value: 1, _end: b';' }, Row { name: b"b", _sep: b',', value: 2, _end: b';' }],
+    }));
+
+    assert!(parse_only(Row::parse, b"foo;123;").is_err());
+    assert_eq!(parse_only(Record::parse, b"\x00\x01\x00\x02\x00\x03"), Err(ParseError::Incomplete(2)));
+}
+
+#[test]
+fn enums() {
+    assert_eq!(parse_only(Message::parse, b"\x01"), Ok(Message::Ping));
+    assert_eq!(parse_only(Message::parse, b"\x02\x00\x07\x00\x01\x00\x09"),
+               Ok(Message::Data(Record { header: Header { kind: 7, len: 1 }, data: vec![9] })));
+    assert_eq!(parse_only(Message::parse, b"h\x00\x01\x00\x02"), Ok(Message::Pair { a: 1, b: 2 }));
+
+    match parse_only(Message::parse, b"\x03\x00") {
+        Err(ParseError::Error(b, _)) => assert_eq!(b, b"\x03\x00"),
+        r                            => panic!("unexpected result: {:?}", r),
+    }
+
+    assert_eq!(parse_only(Wide::parse, b"\x01\x02"), Ok(Wide::A));
+    assert_eq!(parse_only(Wide::parse, b"\x03\x04\x00\x05"), Ok(Wide::B(5)));
+    assert!(parse_only(Wide::parse, b"\x01\x03").is_err());
+
+    assert_eq!(parse_only(Method::parse, b"GET"), Ok(Method::Get));
+    assert_eq!(parse_only(Method::parse, b"POST 12;"), Ok(Method::Post(12)));
+    assert!(parse_only(Method::parse, b"PUT").is_err());
+}
+
+#[test]
+fn hygiene() {
+    assert_eq!(parse_only(Names::parse, b"\x00\x01\x00\x01\x00\x03\x00\x04"), Ok(Names { i: 1, n: 1, t: vec![3, 4] }));
+    assert_eq!(parse_only(Tagged::parse, b"\x01\x00\x05\x00\x06"), Ok(Tagged::A { n: 5, i: 6 }));
+    assert_eq!(parse_only(Tagged::parse, b"\x02\x00\x00\x00\x01\x00\x09"), Ok(Tagged::B(Names { i: 0, n: 1, t: vec![9] })));
+}
+
+#[test]
+fn counts() {
+    assert_eq!(parse_only(Counted::parse, b"\x00\x02\x00\x07\x00\x08\x00\x01\x00\x02"),
+               Ok(Counted { len: 2, none: vec![], values: vec![7, 8], headers: vec![Header { kind: 1, len: 2 }] }));
+    assert_eq!(parse_only(Counted::parse, b"\x00\x01\x00\x07"), Ok(Counted { len: 1, none: vec![], values: vec![7], headers: vec![] }));
+    assert_eq!(parse_only(Counted::parse, b"\x00\x02\x00\x07"), Err(ParseError::Incomplete(2)));
+}
+
+#[test]
+fn tag_paths() {
+    assert_eq!(parse_only(Kind::parse, b"\x0a\x00"), Ok(Kind::Empty));
+    assert_eq!(parse_only(Kind::parse, b"\x0b\x001:\x00\x01\x00\x02\x00\x03\x00\x04"),
+               Ok(Kind::List { len: 1, _sep: b':', items: vec![Header { kind: 1, len: 2 }, Header { kind: 3, len: 4 }] }));
+
+    match parse_only(Kind::parse, b"\x0c\x00") {
+        Err(ParseError::Error(b, _)) => assert_eq!(b, b"\x0c\x00"),
+        r                            => panic!("unexpected result: {:?}", r),
+    }
+}
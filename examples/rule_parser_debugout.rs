@@ -8,11 +8,10 @@
 //! Accept: text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8
 //!
 //! ```
+//!
+//! Run with `cargo run --features trace --example rule_parser_debugout -- examples/rules.txt` to
+//! see the execution of the parsers printed to stderr.
 
-
-#![feature(trace_macros)]
-
-#[macro_use]
 extern crate chomp;
 
 use std::fs::File;
@@ -21,6 +20,7 @@ use std::env;
 use chomp::*;
 
 use chomp::buffer::{Source, Stream, StreamError};
+use chomp::trace::trace;
 
 
 pub struct Rule {
@@ -37,22 +37,6 @@ pub enum Constraint {
     Not(Box<Constraint>)
 }
 
-fn print_indented(i: u32, s: &str) {
-    let mut pad = String::new();
-    for _ in 0..i {
-        pad.push_str("  ");
-    }
-    println!("{}{}", pad, s);
-}
-
-// fn parser_print(i: Input<u8>, s: &str) {
-//     println!("{}", s);
-//     parse!{i;
-//         peek_next();
-//         ret Constraint::Id("any".to_string())
-//     }
-// }
-
 fn is_horizontal_space(c: u8) -> bool { c == b' ' || c == b'\t' }
 fn is_space(c: u8)            -> bool { c == b' ' }
 fn is_not_space(c: u8)        -> bool { c != b' ' }
@@ -68,102 +52,92 @@ fn end_of_line(i: Input<u8>) -> U8Result<u8> {
           |i| token(i, b'\n'))
 }
 
-fn identifier(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "identifier");
-    parse!{i;
+fn identifier(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "identifier", |i| parse!{i;
                 take_while(is_space);
         let n = take_while1(is_identifier_char);
-        // parser_print("hithere");
 
-        ret Constraint::Id("identifier".to_string())
-    }
+        ret Constraint::Id(String::from_utf8_lossy(n).into_owned())
+    })
 }
 
-fn parentheses(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "parentheses");
-    parse!{i;
+fn parentheses(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "parentheses", |i| parse!{i;
                 take_while(is_space);
                 token(b'(');
-        let c = constraint(indent+1);
+        let c = constraint();
                 take_while(is_space);
                 token(b')');
 
         ret c
-    }
+    })
 }
 
-fn not(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "not");
-    parse!{i;
+fn not(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "not", |i| parse!{i;
                 take_while(is_space);
                 token(b'!');
-        let c = constraint(indent+1);
+        let c = constraint();
 
         ret Constraint::Not(Box::new(c))
-    }
+    })
 }
 
-fn unary(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "unary");
-    parse!{i;
-                not(indent+1)
-                <|> parentheses(indent+1)
-                <|> identifier(indent+1)
-    }
+fn unary(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "unary", |i| parse!{i;
+                not()
+                <|> parentheses()
+                <|> identifier()
+    })
 }
 
-fn conjunction(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "conjunction");
-    parse!{i;
-        let first = unary(indent+1);
+fn conjunction(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "conjunction", |i| parse!{i;
+        let first = unary();
                     take_while(is_space);
                     token(b'.');
-        let other = conjunction(indent+1);
+        let other = conjunction();
 
         ret Constraint::Or(
             Box::new(first),
             Box::new(other)
         )
-    }
+    })
 }
 
-fn conjunction_or_unary(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "conjunction_or_unary");
-    parse!{i;
-        conjunction(indent+1)
-        <|> unary(indent+1)
-    }
+fn conjunction_or_unary(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "conjunction_or_unary", |i| parse!{i;
+        conjunction()
+        <|> unary()
+    })
 }
 
-fn disjunction(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "disjunction");
-    parse!{i;
-        let first = conjunction_or_unary(indent+1);
+fn disjunction(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "disjunction", |i| parse!{i;
+        let first = conjunction_or_unary();
                     take_while(is_space);
                     token(b'|');
-        let other = disjunction(indent+1);
+        let other = disjunction();
 
         ret Constraint::Or(
             Box::new(first),
             Box::new(other)
         )
-    }
+    })
 }
 
-fn binary(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "binary");
-    parse!{i;
-        disjunction(indent+1)
-        <|> conjunction(indent+1)
-    }
+fn binary(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "binary", |i| parse!{i;
+        disjunction()
+        <|> conjunction()
+    })
 }
 
-fn constraint(i: Input<u8>, indent: u32) -> U8Result<Constraint> {
-    print_indented(indent, "constraint");
-    parse!{i;
-        binary(indent+1)
-        <|> unary(indent+1)
-    }
+fn constraint(i: Input<u8>) -> U8Result<Constraint> {
+    trace(i, "constraint", |i| parse!{i;
+        binary()
+        <|> unary()
+    })
 }
 
 fn errParser(i: Input<u8>) -> U8Result<Constraint> {
@@ -173,13 +147,12 @@ fn errParser(i: Input<u8>) -> U8Result<Constraint> {
 }
 
 fn rule(i: Input<u8>) -> U8Result<Rule> {
-    // trace_macros!(true);
-    parse!{i;
+    trace(i, "rule", |i| parse!{i;
                   take_while(is_space);
         let src = take_while1(is_identifier_char);
                   take_while(is_space);
                   token(b':');
-        let c   = constraint(0);
+        let c   = constraint();
                   take_while(is_space);
                   token(b':');
                   take_while(is_space);
@@ -187,9 +160,12 @@ fn rule(i: Input<u8>) -> U8Result<Rule> {
                   take_while(is_space);
                   take_while(is_end_of_line);
 
-        ret Rule { src: "hithere".to_string(), dest: "hithere".to_string(), constraint: Constraint::Id("hithere".to_string()) }
-    }
-    // trace_macros!(false);
+        ret Rule {
+            src:        String::from_utf8_lossy(src).into_owned(),
+            dest:       String::from_utf8_lossy(des).into_owned(),
+            constraint: c,
+        }
+    })
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,564 @@
+//! Asynchronous counterpart to `Source`, reading from a `futures_io::AsyncRead`.
+
+use std::cmp;
+use std::io;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_io::AsyncRead;
+
+use {Input, ParseResult};
+use primitives::input;
+use primitives::{InputBuffer, State, IntoInner};
+
+use buffer::{
+    Buffer,
+    DataSource,
+    ErrorPolicy,
+    FixedSizeBuffer,
+    StreamError,
+};
+
+bitflags!{
+    flags ParserState: u64 {
+        /// The parser which was last run on the buffer did not manage to complete with the data
+        /// available in the buffer.
+        const INCOMPLETE   = 1,
+        /// The buffer did not manage to read any more data from the underlying `AsyncRead`
+        /// implementation.
+        const END_OF_INPUT = 2,
+        /// The error policy is skipping data until it finds a delimiter.
+        const SKIPPING     = 4,
+    }
+}
+
+/// Manages a buffer and `AsyncRead` pair, awaiting more data whenever a parser needs it.
+///
+/// This is the asynchronous counterpart to `Source`, instead of returning `StreamError::Retry`
+/// the future returned from `parse()` will read more data and rerun the parser until it either
+/// completes or the reader reaches its end.
+///
+/// Readers implementing `tokio::io::AsyncRead` can be used through the compatibility wrappers in
+/// `tokio-util`.
+///
+/// ```
+/// extern crate chomp;
+/// extern crate futures;
+///
+/// use futures::executor::block_on;
+/// use futures::io::Cursor;
+///
+/// use chomp::buffer::AsyncSource;
+/// use chomp::{token, ascii};
+///
+/// # fn main() {
+/// let mut src = AsyncSource::new(Cursor::new(&b"123,456"[..]));
+///
+/// assert_eq!(block_on(src.parse(ascii::decimal::<u32>)), Ok(123));
+/// assert_eq!(block_on(src.parse(|i| token(i, b','))), Ok(b','));
+/// assert_eq!(block_on(src.parse(ascii::decimal::<u32>)), Ok(456));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncSource<R, B: Buffer<u8>> {
+    /// Source reader
+    reader:   R,
+    /// Temporary source
+    buffer:   B,
+    /// The requested amount of bytes to be available for reading from the buffer
+    request:  usize,
+    /// Number of bytes read by a fill which has not yet completed
+    read:     usize,
+    /// Number of bytes consumed from the start of the stream
+    consumed: u64,
+    /// Input state, if end has been reached
+    state:    ParserState,
+    /// What to do with the buffer when a parser fails
+    policy:   ErrorPolicy<u8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncSource<R, FixedSizeBuffer<u8>> {
+    /// Creates a new `AsyncSource` from an `AsyncRead` instance with the default
+    /// `FixedSizeBuffer` settings.
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self::with_buffer(reader, FixedSizeBuffer::new())
+    }
+}
+
+impl<R: AsyncRead + Unpin, B: Buffer<u8>> AsyncSource<R, B> {
+    /// Creates a new `AsyncSource` from `AsyncRead` and `Buffer` instances.
+    #[inline]
+    pub fn with_buffer(reader: R, buffer: B) -> Self {
+        AsyncSource {
+            reader:   reader,
+            buffer:   buffer,
+            request:  0,
+            read:     0,
+            consumed: 0,
+            state:    INCOMPLETE,
+            policy:   ErrorPolicy::default(),
+        }
+    }
+
+    /// Attempts to fill the buffer to satisfy the last call to `parse()`, returning the number of
+    /// bytes read once the request has been satisfied or the reader has reached its end.
+    pub fn poll_fill(&mut self, cx: &mut Context) -> Poll<io::Result<usize>> {
+        // Make sure we actually try to read something in case the buffer is empty
+        let req = cmp::max(1, self.request);
+
+        if self.buffer.len() < req {
+            let diff = req - self.buffer.len();
+
+            self.buffer.request_space(diff);
+
+            let mut source = PollRead {
+                reader:  Pin::new(&mut self.reader),
+                cx:      cx,
+                pending: false,
+            };
+
+            while self.buffer.len() < req {
+                match self.buffer.fill(&mut source) {
+                    Ok(0)                      => break,
+                    Ok(n)                      => self.read += n,
+                    Err(_) if source.pending   => return Poll::Pending,
+                    Err(e)                     => {
+                        self.read = 0;
+
+                        return Poll::Ready(Err(e));
+                    },
+                }
+            }
+        }
+
+        self.state.remove(INCOMPLETE);
+
+        if self.buffer.len() >= req {
+            self.state.remove(END_OF_INPUT);
+        } else {
+            self.state.insert(END_OF_INPUT);
+        }
+
+        let n = self.read;
+
+        self.read = 0;
+
+        Poll::Ready(Ok(n))
+    }
+
+    /// Attempts to fill the buffer to satisfy the last call to `parse()`.
+    #[inline]
+    pub fn fill<'a>(&'a mut self) -> Fill<'a, R, B> {
+        Fill { source: self }
+    }
+
+    /// Runs the parser `f` on the data in the buffer, awaiting more data from the reader whenever
+    /// the parser is incomplete.
+    ///
+    /// The resulting future never resolves to `StreamError::Retry`, `StreamError::Incomplete` is
+    /// only returned if the reader reached its end (or the buffer is full) before the parser
+    /// completed.
+    ///
+    /// # Note
+    ///
+    /// The parser is run again from the start every time more data has been read, so unlike
+    /// `Source::parse` the value and error it produces cannot borrow from the buffer.
+    #[inline]
+    pub fn parse<'a, F, T, E>(&'a mut self, f: F) -> Parse<'a, R, B, F>
+      where F: FnMut(Input<u8>) -> ParseResult<u8, T, E> {
+        Parse {
+            source: Some(self),
+            parser: f,
+        }
+    }
+
+    /// Returns the number of bytes left in the buffer which have not yet been parsed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// If the buffer is empty and the reader has reached the end.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.state.contains(END_OF_INPUT) && self.len() == 0
+    }
+
+    /// Returns the offset of the start of the buffer from the start of the stream.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Returns the capacity of the underlying buffer.
+    ///
+    /// This is the maximum number of bytes the buffer can store.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Borrows the remainder of the buffer.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Changes what happens to the buffer when a parser fails, see `ErrorPolicy`.
+    ///
+    /// When skipping to a delimiter which is not in the buffer the next call to `parse()` keeps
+    /// reading and skipping data until it finds the delimiter.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy<u8>) {
+        self.policy = policy
+    }
+
+    /// Consumes self to reveal the underlying `AsyncRead` instance, any buffered data is lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Future returned by `AsyncSource::fill`.
+#[derive(Debug)]
+pub struct Fill<'a, R: 'a, B: 'a + Buffer<u8>> {
+    source: &'a mut AsyncSource<R, B>,
+}
+
+impl<'a, R: AsyncRead + Unpin, B: Buffer<u8>> Future for Fill<'a, R, B> {
+    type Output = io::Result<usize>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.get_mut().source.poll_fill(cx)
+    }
+}
+
+/// Future returned by `AsyncSource::parse`.
+#[derive(Debug)]
+pub struct Parse<'a, R: 'a, B: 'a + Buffer<u8>, F> {
+    /// The source, taken once the future has completed
+    source: Option<&'a mut AsyncSource<R, B>>,
+    parser: F,
+}
+
+// The parser is never pinned, it is only called through a plain mutable borrow.
+impl<'a, R, B: Buffer<u8>, F> Unpin for Parse<'a, R, B, F> {}
+
+impl<'a, R, B, F, T, E> Future for Parse<'a, R, B, F>
+  where R: AsyncRead + Unpin,
+        B: Buffer<u8>,
+        F: FnMut(Input<u8>) -> ParseResult<u8, T, E> {
+    type Output = Result<T, StreamError<'a, u8, E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            let (pos, err) = {
+                let src = this.source.as_mut().expect("Parse polled after completion");
+
+                if src.state.contains(INCOMPLETE) {
+                    match src.poll_fill(cx) {
+                        Poll::Pending       => return Poll::Pending,
+                        Poll::Ready(Err(e)) => {
+                            this.source = None;
+
+                            return Poll::Ready(Err(StreamError::IoError(e)));
+                        },
+                        Poll::Ready(Ok(_))  => {},
+                    }
+                }
+
+                if src.state.contains(SKIPPING) {
+                    match src.policy.skip(&src.buffer) {
+                        Some(n) => {
+                            src.buffer.consume(n);
+                            src.consumed += n as u64;
+                            src.state.remove(SKIPPING);
+                        },
+                        None    => {
+                            src.consumed += src.buffer.len() as u64;
+                            src.buffer.consume(src.buffer.len());
+
+                            if !src.state.contains(END_OF_INPUT) {
+                                src.request = 0;
+                                src.state.insert(INCOMPLETE);
+
+                                continue;
+                            }
+
+                            src.state.remove(SKIPPING);
+                        },
+                    }
+                }
+
+                if src.is_empty() {
+                    this.source = None;
+
+                    return Poll::Ready(Err(StreamError::EndOfInput));
+                }
+
+                let input_state = if src.state.contains(END_OF_INPUT) { input::END_OF_INPUT } else { input::DEFAULT };
+
+                match (this.parser)(input::new(input_state, &src.buffer)).into_inner() {
+                    State::Data(remainder, data) => {
+                        let n = src.buffer.len() - remainder.buffer().len();
+
+                        src.buffer.consume(n);
+                        src.consumed += n as u64;
+
+                        this.source = None;
+
+                        return Poll::Ready(Ok(data));
+                    },
+                    State::Error(remainder, err) => (src.buffer.len() - remainder.len(), err),
+                    State::Incomplete(n) => {
+                        src.request = src.buffer.len() + n;
+
+                        if src.state.contains(END_OF_INPUT) {
+                            let request = src.request;
+                            let offset  = src.consumed;
+
+                            this.source = None;
+
+                            return Poll::Ready(Err(StreamError::Incomplete(request, offset)));
+                        }
+
+                        src.state.insert(INCOMPLETE);
+
+                        continue;
+                    },
+                }
+            };
+
+            let src    = this.source.take().expect("Parse polled after completion");
+            let offset = src.consumed + pos as u64;
+            let n      = match src.policy.on_error(&src.buffer, pos) {
+                Some(n) => n,
+                None    => {
+                    // Keep skipping on the next call if there is more data
+                    if !src.state.contains(END_OF_INPUT) {
+                        src.state.insert(SKIPPING | INCOMPLETE);
+                        src.request = 0;
+                    }
+
+                    src.buffer.len()
+                },
+            };
+
+            src.consumed += n as u64;
+
+            // The remainder of the error borrows the buffer for the lifetime of the future,
+            // consuming does not invalidate the borrow.
+            let src: &'a AsyncSource<R, B> = src;
+            let remainder = &src.buffer[pos..];
+
+            src.buffer.consume(n);
+
+            return Poll::Ready(Err(StreamError::ParseError(remainder, err, offset)));
+        }
+    }
+}
+
+/// `DataSource` reading from an `AsyncRead` within a call to `poll`, `Poll::Pending` is reported
+/// as an error of the kind `WouldBlock`.
+struct PollRead<'r, 'c, 'w: 'c, R: 'r> {
+    reader:  Pin<&'r mut R>,
+    cx:      &'c mut Context<'w>,
+    /// If the reader returned `Poll::Pending`, the waker has been registered
+    pending: bool,
+}
+
+impl<'r, 'c, 'w, R: AsyncRead> DataSource for PollRead<'r, 'c, 'w, R> {
+    type Item = u8;
+
+    #[inline]
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self.reader.as_mut().poll_read(self.cx, buffer) {
+            Poll::Ready(r) => r,
+            Poll::Pending  => {
+                self.pending = true;
+
+                Err(io::ErrorKind::WouldBlock.into())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::executor::block_on;
+    use futures::io::{AsyncRead, Cursor};
+
+    use {any, take, token};
+    use Error;
+    use ascii::decimal;
+    use buffer::{
+        ErrorPolicy,
+        FixedSizeBuffer,
+        GrowingBuffer,
+        StreamError,
+    };
+
+    use super::*;
+
+    /// Reader yielding at most `chunk` bytes per read, returning `Pending` before every read.
+    struct Chunked<'a> {
+        data:    &'a [u8],
+        chunk:   usize,
+        pending: bool,
+        reads:   usize,
+    }
+
+    impl<'a> Chunked<'a> {
+        fn new(data: &'a [u8], chunk: usize) -> Self {
+            Chunked { data: data, chunk: chunk, pending: true, reads: 0 }
+        }
+    }
+
+    impl<'a> AsyncRead for Chunked<'a> {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            if self.pending {
+                self.pending = false;
+
+                cx.waker().wake_by_ref();
+
+                return Poll::Pending;
+            }
+
+            let n = *[self.chunk, buf.len(), self.data.len()].iter().min().unwrap();
+
+            buf[..n].copy_from_slice(&self.data[..n]);
+
+            self.data    = &self.data[n..];
+            self.pending = true;
+            self.reads  += 1;
+
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    struct Failing;
+
+    impl AsyncRead for Failing {
+        fn poll_read(self: Pin<&mut Self>, _: &mut Context, _: &mut [u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Err(io::Error::other("failing")))
+        }
+    }
+
+    fn take3(i: Input<u8>) -> ParseResult<u8, Vec<u8>, Error<u8>> {
+        take(i, 3).map(|b| b.to_vec())
+    }
+
+    #[test]
+    fn parse() {
+        let mut s = AsyncSource::new(Cursor::new(&b"abcdefg"[..]));
+
+        assert_eq!(block_on(s.parse(take3)), Ok(b"abc".to_vec()));
+        assert_eq!(block_on(s.parse(take3)), Ok(b"def".to_vec()));
+        assert_eq!(block_on(s.parse(take3)), Err(StreamError::Incomplete(3, 6)));
+        assert_eq!(block_on(s.parse(any)), Ok(b'g'));
+        assert_eq!(block_on(s.parse(any)), Err(StreamError::EndOfInput));
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut s = AsyncSource::new(Cursor::new(&b"ab"[..]));
+
+        let err = match token(input::new(input::DEFAULT, b"a"), b'b').into_inner() {
+            State::Error(_, e) => e,
+            r                  => panic!("unexpected result: {:?}", r),
+        };
+
+        assert_eq!(block_on(s.parse(|i| token(i, b'b'))), Err(StreamError::ParseError(&b"ab"[..], err, 0)));
+        assert_eq!(block_on(s.parse(any)), Ok(b'a'));
+    }
+
+    #[test]
+    fn error_policy() {
+        fn number(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+            decimal(i).bind(|i, n| token(i, b',').map(|_| n))
+        }
+
+        let mut s = AsyncSource::new(Cursor::new(&b"1;2,"[..]));
+
+        s.set_error_policy(ErrorPolicy::rollback());
+
+        match block_on(s.parse(number)) {
+            Err(StreamError::ParseError(b, _, 1)) => assert_eq!(b, b";2,"),
+            r                                     => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(s.buffer(), b"1;2,");
+        assert_eq!(s.offset(), 0);
+
+        // The delimiter arrives in a later read
+        let mut s = AsyncSource::with_buffer(Chunked::new(b"1;2aa,3,", 2), FixedSizeBuffer::with_size(4));
+
+        s.set_error_policy(ErrorPolicy::skip_to(b','));
+
+        match block_on(s.parse(number)) {
+            Err(StreamError::ParseError(b, _, 1)) => assert_eq!(b, b";"),
+            r                                     => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(block_on(s.parse(number)), Ok(3));
+        assert_eq!(s.offset(), 8);
+    }
+
+    #[test]
+    fn awaits_data() {
+        let mut s = AsyncSource::with_buffer(Chunked::new(b"12345;", 2), FixedSizeBuffer::with_size(8));
+
+        assert_eq!(block_on(s.parse(decimal::<u32>)), Ok(12345));
+        assert_eq!(block_on(s.parse(any)), Ok(b';'));
+        assert_eq!(block_on(s.parse(any)), Err(StreamError::EndOfInput));
+        // The last read is the one reaching the end
+        assert_eq!(s.into_inner().reads, 4);
+    }
+
+    #[test]
+    fn awaits_data_growing() {
+        let data = b"1234567890123456789;";
+        let mut s = AsyncSource::with_buffer(Chunked::new(data, 3), GrowingBuffer::new());
+
+        assert_eq!(block_on(s.parse(decimal::<u64>)), Ok(1234567890123456789));
+        assert_eq!(s.buffer(), b";");
+    }
+
+    #[test]
+    fn fill() {
+        let mut s = AsyncSource::with_buffer(Chunked::new(b"abc", 2), FixedSizeBuffer::with_size(8));
+
+        assert_eq!(s.len(), 0);
+        assert_eq!(block_on(s.fill()).unwrap(), 2);
+        assert_eq!(s.buffer(), b"ab");
+        assert!(!s.is_empty());
+    }
+
+    #[test]
+    fn full_buffer() {
+        let mut s = AsyncSource::with_buffer(Chunked::new(b"abcdef", 4), FixedSizeBuffer::with_size(4));
+
+        assert_eq!(block_on(s.parse(|i| take(i, 6).map(|b| b.to_vec()))), Err(StreamError::Incomplete(6, 0)));
+        assert_eq!(s.capacity(), 4);
+    }
+
+    #[test]
+    fn io_error() {
+        let mut s = AsyncSource::new(Failing);
+
+        match block_on(s.parse(any)) {
+            Err(StreamError::IoError(e)) => assert_eq!(e.to_string(), "failing"),
+            r                            => panic!("unexpected result: {:?}", r),
+        }
+    }
+}
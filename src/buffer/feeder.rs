@@ -0,0 +1,481 @@
+//! Push-based parsing of data arriving in chunks, eg. from event-loop callbacks.
+
+use std::cmp;
+use std::fmt;
+use std::io;
+
+use {Input, ParseResult};
+use primitives::input;
+use primitives::{InputBuffer, State, IntoInner};
+
+use buffer::{
+    Buffer,
+    DataSource,
+    ErrorPolicy,
+    GrowingBuffer,
+    IterError,
+};
+
+bitflags!{
+    flags FeederState: u64 {
+        /// `finish()` has been called, no more data will be fed.
+        const END_OF_INPUT = 1,
+        /// The error policy is skipping data until it finds a delimiter.
+        const SKIPPING     = 2,
+    }
+}
+
+/// Runs a parser on data pushed to it in chunks, the push-based counterpart of `Source`.
+///
+/// Each call to `feed` copies the data into the internal buffer and runs the parser as many times
+/// as it can complete, returning the parsed items. The unparsed tail is kept in the buffer until
+/// more data arrives. The parser is run again from the start once more data is available, so the
+/// items and errors it produces cannot borrow from the buffer.
+///
+/// Until `finish` is called the parser is given input which is not the last slice, just like
+/// `Source` before its `DataSource` is exhausted, `finish` then runs the parser on the remaining
+/// data as the final slice of input.
+///
+/// ```
+/// use chomp::{Input, ParseResult, Error, token};
+/// use chomp::ascii::decimal;
+/// use chomp::buffer::{Feeder, IterError};
+///
+/// fn number(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+///     decimal(i).bind(|i, n| token(i, b',').map(|_| n))
+/// }
+///
+/// let mut f = Feeder::new(number);
+///
+/// assert_eq!(f.feed(b"1,2"), vec![Ok(1)]);
+/// assert_eq!(f.feed(b"2,333,4"), vec![Ok(22), Ok(333)]);
+/// assert_eq!(f.buffer(), b"4");
+/// assert_eq!(f.finish(), vec![Err(IterError::Incomplete(2, 9))]);
+/// ```
+pub struct Feeder<I: Copy, B: Buffer<I>, F> {
+    /// Unparsed data
+    buffer:   B,
+    parser:   F,
+    /// The number of items the parser needs before it is worth running it again
+    request:  usize,
+    /// If the input has ended and the policy is skipping
+    state:    FeederState,
+    /// What to do with the buffer when the parser fails
+    policy:   ErrorPolicy<I>,
+    /// Number of items consumed from the start of the stream
+    consumed: u64,
+}
+
+impl<I: Copy, F> Feeder<I, GrowingBuffer<I>, F> {
+    /// Creates a new `Feeder` running ``parser`` with an unlimited `GrowingBuffer`.
+    #[inline]
+    pub fn new<T, E>(parser: F) -> Self
+      where F: FnMut(Input<I>) -> ParseResult<I, T, E> {
+        Self::with_buffer(GrowingBuffer::new(), parser)
+    }
+}
+
+impl<I: Copy, B: Buffer<I>, F> Feeder<I, B, F> {
+    /// Creates a new `Feeder` running ``parser``, storing unparsed data in ``buffer``.
+    #[inline]
+    pub fn with_buffer<T, E>(buffer: B, parser: F) -> Self
+      where F: FnMut(Input<I>) -> ParseResult<I, T, E> {
+        Feeder {
+            buffer:   buffer,
+            parser:   parser,
+            request:  0,
+            state:    FeederState::empty(),
+            policy:   ErrorPolicy::default(),
+            consumed: 0,
+        }
+    }
+
+    /// Appends ``data`` to the buffer and runs the parser as far as it can get, returning the
+    /// parsed items and errors in order.
+    ///
+    /// After an error the parser continues after the input consumed by the `ErrorPolicy`. If the
+    /// parser fails without the policy consuming anything, or succeeds without consuming anything,
+    /// it is not run again until the next call to `feed` or `finish`.
+    ///
+    /// If the parser requires more data than the buffer can hold an `IterError::Incomplete` is
+    /// returned and the contents of the buffer are discarded to make room for the rest of
+    /// ``data``. If the buffer is full of data the parser has stopped on an `IterError::IoError`
+    /// with `io::ErrorKind::WriteZero` is returned and the rest of ``data`` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `finish` has already been called.
+    pub fn feed<T, E>(&mut self, data: &[I]) -> Vec<Result<T, IterError<E>>>
+      where F: FnMut(Input<I>) -> ParseResult<I, T, E> {
+        assert!(!self.state.contains(END_OF_INPUT), "Feeder::feed called after Feeder::finish");
+
+        let mut items   = Vec::new();
+        let mut source  = SliceSource(data);
+        let mut stalled = false;
+
+        while !source.0.is_empty() {
+            self.buffer.request_space(source.0.len());
+
+            // Copying from a slice cannot fail
+            let n = self.buffer.fill(&mut source).unwrap_or(0);
+
+            if n == 0 {
+                if self.request <= self.buffer.len() {
+                    // The buffer is full of data the policy decided to keep
+                    items.push(Err(IterError::IoError(io::Error::new(io::ErrorKind::WriteZero, "buffer is full"))));
+
+                    break;
+                }
+
+                // The parser needs more than the buffer can hold
+                items.push(Err(IterError::Incomplete(self.request, self.consumed)));
+
+                self.discard(self.buffer.len());
+                self.request = 0;
+
+                continue;
+            }
+
+            if !stalled {
+                stalled = !self.run(&mut items);
+            }
+        }
+
+        items
+    }
+
+    /// Marks the end of the input and runs the parser on the remaining data, returning the parsed
+    /// items and errors in order.
+    ///
+    /// If the parser cannot complete with the remaining data an `IterError::Incomplete` is
+    /// returned and the data is left in the buffer.
+    ///
+    /// # Panics
+    ///
+    /// If `finish` has already been called.
+    pub fn finish<T, E>(&mut self) -> Vec<Result<T, IterError<E>>>
+      where F: FnMut(Input<I>) -> ParseResult<I, T, E> {
+        assert!(!self.state.contains(END_OF_INPUT), "Feeder::finish called twice");
+
+        let mut items = Vec::new();
+
+        self.state.insert(END_OF_INPUT);
+        self.run(&mut items);
+
+        items
+    }
+
+    /// Runs the parser until it needs more data, returns false if it stopped without consuming
+    /// anything and should not be run again on the same data.
+    fn run<T, E>(&mut self, items: &mut Vec<Result<T, IterError<E>>>) -> bool
+      where F: FnMut(Input<I>) -> ParseResult<I, T, E> {
+        let end = self.state.contains(END_OF_INPUT);
+
+        loop {
+            if self.state.contains(SKIPPING) {
+                match self.policy.skip(&self.buffer) {
+                    Some(n) => {
+                        self.discard(n);
+                        self.state.remove(SKIPPING);
+                    },
+                    None    => {
+                        self.discard(self.buffer.len());
+
+                        if !end {
+                            return true;
+                        }
+
+                        self.state.remove(SKIPPING);
+                    },
+                }
+            }
+
+            if self.buffer.is_empty() || (!end && self.buffer.len() < self.request) {
+                return true;
+            }
+
+            let input_state = if end { input::END_OF_INPUT } else { input::DEFAULT };
+
+            match (self.parser)(input::new(input_state, &self.buffer)).into_inner() {
+                State::Data(remainder, data) => {
+                    let n = self.buffer.len() - remainder.buffer().len();
+
+                    self.discard(n);
+                    self.request = 0;
+
+                    items.push(Ok(data));
+
+                    // The parser would succeed the same way again
+                    if n == 0 {
+                        return false;
+                    }
+                },
+                State::Error(remainder, err) => {
+                    let pos    = self.buffer.len() - remainder.len();
+                    let offset = self.consumed + pos as u64;
+                    let n      = match self.policy.on_error(&self.buffer, pos) {
+                        Some(n) => n,
+                        None    => {
+                            // Keep skipping when more data arrives
+                            if !end {
+                                self.state.insert(SKIPPING);
+                            }
+
+                            self.buffer.len()
+                        },
+                    };
+
+                    self.discard(n);
+                    self.request = 0;
+
+                    items.push(Err(IterError::ParseError(err, offset)));
+
+                    // The parser would fail the same way again
+                    if n == 0 {
+                        return false;
+                    }
+                },
+                State::Incomplete(n) => {
+                    self.request = self.buffer.len() + n;
+
+                    if end {
+                        items.push(Err(IterError::Incomplete(self.request, self.consumed)));
+                    }
+
+                    return true;
+                },
+            }
+        }
+    }
+
+    /// Consumes ``n`` items from the buffer.
+    #[inline]
+    fn discard(&mut self, n: usize) {
+        self.buffer.consume(n);
+        self.consumed += n as u64;
+    }
+
+    /// Returns the number of items in the buffer which have not yet been parsed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// If the buffer is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Returns the offset of the start of the buffer from the start of the stream.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Returns the capacity of the underlying buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Borrows the unparsed data.
+    #[inline]
+    pub fn buffer(&self) -> &[I] {
+        &self.buffer
+    }
+
+    /// If `finish` has been called.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.state.contains(END_OF_INPUT)
+    }
+
+    /// Changes what happens to the buffer when the parser fails, see `ErrorPolicy`.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy<I>) {
+        self.policy = policy
+    }
+}
+
+impl<I, B, F> fmt::Debug for Feeder<I, B, F>
+  where I: Copy + fmt::Debug,
+        B: Buffer<I> + fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Feeder")
+            .field("buffer", &self.buffer)
+            .field("request", &self.request)
+            .field("state", &self.state)
+            .field("policy", &self.policy)
+            .field("consumed", &self.consumed)
+            .finish()
+    }
+}
+
+/// `DataSource` copying the items of a slice, advancing past the copied items.
+struct SliceSource<'a, I: 'a>(&'a [I]);
+
+impl<'a, I: Copy> DataSource for SliceSource<'a, I> {
+    type Item = I;
+
+    #[inline]
+    fn read(&mut self, buffer: &mut [I]) -> io::Result<usize> {
+        let n = cmp::min(buffer.len(), self.0.len());
+
+        buffer[..n].copy_from_slice(&self.0[..n]);
+
+        self.0 = &self.0[n..];
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use {Error, Input, ParseResult, take, take_while, token};
+    use ascii::decimal;
+    use buffer::{ErrorPolicy, FixedSizeBuffer, IterError};
+
+    use super::*;
+
+    fn number(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+        decimal(i).bind(|i, n| token(i, b',').map(|_| n))
+    }
+
+    #[test]
+    fn chunks() {
+        let mut f = Feeder::new(number);
+
+        assert_eq!(f.feed(b""), vec![]);
+        assert_eq!(f.feed(b"1"), vec![]);
+        assert_eq!(f.feed(b"2"), vec![]);
+        assert_eq!(f.feed(b",3,4"), vec![Ok(12), Ok(3)]);
+        assert_eq!(f.len(), 1);
+        assert_eq!(f.offset(), 5);
+        assert_eq!(f.feed(b"5,"), vec![Ok(45)]);
+        assert!(f.is_empty());
+        assert!(!f.is_finished());
+        assert_eq!(f.finish(), vec![]);
+        assert!(f.is_finished());
+    }
+
+    #[test]
+    fn end_of_input() {
+        // Without the end of input take_while can not know if there is more data
+        let mut f = Feeder::new(decimal::<u32>);
+
+        assert_eq!(f.feed(b"12"), vec![]);
+        assert_eq!(f.feed(b"3"), vec![]);
+        assert_eq!(f.finish(), vec![Ok(123)]);
+        assert!(f.is_empty());
+    }
+
+    #[test]
+    fn incomplete() {
+        let mut f = Feeder::new(|i| take(i, 3).map(|b| b.to_vec()));
+
+        assert_eq!(f.feed(b"abcd"), vec![Ok(b"abc".to_vec())]);
+        assert_eq!(f.finish(), vec![Err(IterError::<Error<u8>>::Incomplete(3, 3))]);
+        assert_eq!(f.buffer(), b"d");
+    }
+
+    #[test]
+    fn waits_for_request() {
+        let mut n = 0;
+
+        {
+            let mut f = Feeder::new(|i| { n += 1; take(i, 4).map(|b| b.len()) });
+
+            assert_eq!(f.feed(b"a"), vec![]);
+            assert_eq!(f.feed(b"b"), vec![]);
+            assert_eq!(f.feed(b"c"), vec![]);
+            assert_eq!(f.feed(b"d"), vec![Ok::<_, IterError<Error<u8>>>(4)]);
+        }
+
+        // The parser is not run again until the requested amount of data is available
+        assert_eq!(n, 2);
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut f = Feeder::new(number);
+
+        assert_eq!(f.feed(b"1,a2,3"), vec![Ok(1), Err(IterError::ParseError(Error::new(), 2))]);
+        assert_eq!(f.buffer(), b"a2,3");
+        // Fails again since the default policy did not consume anything
+        assert_eq!(f.feed(b","), vec![Err(IterError::ParseError(Error::new(), 2))]);
+
+        let mut f = Feeder::new(number);
+
+        f.set_error_policy(ErrorPolicy::skip_to(b','));
+
+        assert_eq!(f.feed(b"1,a2,3"), vec![Ok(1), Err(IterError::ParseError(Error::new(), 2))]);
+        assert_eq!(f.feed(b","), vec![Ok(3)]);
+        assert_eq!(f.offset(), 7);
+    }
+
+    #[test]
+    fn skip_across_feeds() {
+        let mut f = Feeder::new(number);
+
+        f.set_error_policy(ErrorPolicy::skip_to(b','));
+
+        assert_eq!(f.feed(b"a"), vec![Err(IterError::ParseError(Error::new(), 0))]);
+        assert!(f.is_empty());
+        assert_eq!(f.feed(b"bc"), vec![]);
+        assert_eq!(f.feed(b"d,1,"), vec![Ok(1)]);
+        assert_eq!(f.offset(), 7);
+    }
+
+    #[test]
+    fn fixed_size() {
+        let mut f = Feeder::with_buffer(FixedSizeBuffer::with_size(4), number);
+
+        f.set_error_policy(ErrorPolicy::skip_to(b','));
+
+        // Larger than the buffer, parsed while copying
+        assert_eq!(f.feed(b"1,22,333,4444"), vec![Ok(1), Ok(22), Ok(333)]);
+        assert_eq!(f.buffer(), b"4444");
+        // The number does not fit and is discarded
+        assert_eq!(f.feed(b",5,"), vec![Err(IterError::Incomplete(5, 9)), Err(IterError::ParseError(Error::new(), 13)), Ok(5)]);
+        assert_eq!(f.offset(), 16);
+        assert_eq!(f.finish(), vec![]);
+    }
+
+    #[test]
+    fn no_progress() {
+        let mut f = Feeder::new(|i| take_while(i, |c| c == b'a').map(|b| b.len()));
+
+        assert_eq!(f.feed(b"b"), vec![Ok::<_, IterError<Error<u8>>>(0)]);
+        assert_eq!(f.feed(b"c"), vec![Ok(0)]);
+        assert_eq!(f.buffer(), b"bc");
+        assert_eq!(f.finish(), vec![Ok(0)]);
+    }
+
+    #[test]
+    fn full_after_error() {
+        let mut f = Feeder::with_buffer(FixedSizeBuffer::with_size(4), number);
+
+        f.set_error_policy(ErrorPolicy::rollback());
+
+        let r = f.feed(b"x,1,1,1,1,");
+
+        assert_eq!(r.len(), 2);
+        assert_eq!(r[0], Err(IterError::ParseError(Error::new(), 0)));
+        assert!(match r[1] { Err(IterError::IoError(ref e)) => e.kind() == io::ErrorKind::WriteZero, _ => false });
+        // The data kept by the policy is not discarded
+        assert_eq!(f.buffer(), b"x,1,");
+        assert_eq!(f.offset(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn feed_after_finish() {
+        let mut f = Feeder::new(number);
+
+        let _ = f.finish();
+        let _ = f.feed(b"1,");
+    }
+}
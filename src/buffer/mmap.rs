@@ -0,0 +1,298 @@
+//! Memory-mapped input for parsing large files without copying them through a buffer.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::{Mmap, MmapAsRawDesc};
+
+use {Input, ParseResult};
+
+use buffer::{
+    ErrorPolicy,
+    FixedSizeBuffer,
+    IntoStream,
+    Source,
+    StreamError,
+    Stream,
+};
+use buffer::data_source::ReadDataSource;
+
+/// A file parsed directly from a read-only memory map, falling back to a buffered `Source` if the
+/// file cannot be mapped (eg. pipes and terminals).
+///
+/// When mapped the whole file is exposed as one finite slice, `as_slice` can be used with
+/// `parse_only` or `combinators::iter` and `parse` will never return `StreamError::Retry`.
+///
+/// # Note
+///
+/// The contents of a mapped file must not be modified while the `MmapSource` is alive, changes
+/// made by other processes will be visible through the slice.
+///
+/// ```
+/// use chomp::{parse_only, take_while, take_while1, token};
+/// use chomp::ascii::is_whitespace;
+/// use chomp::buffer::MmapSource;
+///
+/// let src = MmapSource::open("./README.md").unwrap();
+///
+/// let r = parse_only(|i| take_while(i, |c| c != b'#')
+///                        .then(|i| token(i, b'#'))
+///                        .then(|i| take_while1(i, is_whitespace))
+///                        .then(|i| take_while1(i, |c| c != b'\r' && c != b'\n')),
+///                    src.as_slice().unwrap());
+///
+/// assert_eq!(r, Ok(&b"Chomp"[..]));
+/// ```
+#[derive(Debug)]
+pub enum MmapSource<R: io::Read = File> {
+    /// The file is mapped into memory.
+    Mapped {
+        /// The memory map.
+        map:    Mmap,
+        /// Number of bytes parsed from the start of the map.
+        pos:    usize,
+        /// What to do with the input when a parser fails.
+        policy: ErrorPolicy<u8>,
+    },
+    /// The file could not be mapped and is read through a buffer.
+    Buffered(Source<ReadDataSource<R>, FixedSizeBuffer<u8>>),
+}
+
+impl MmapSource<File> {
+    /// Opens the file at `path`, mapping it into memory if possible.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        File::open(path).map(MmapSource::new)
+    }
+}
+
+impl<R: io::Read> MmapSource<R> {
+    /// Attempts to map the supplied reader into memory, falling back to reading it through a
+    /// `Source` with the default `FixedSizeBuffer` settings if mapping fails.
+    ///
+    /// Accepts `File`, `io::Stdin` and other readers backed by a file descriptor (or handle on
+    /// Windows).
+    #[inline]
+    pub fn new(reader: R) -> Self
+      where for<'b> &'b R: MmapAsRawDesc {
+        // Safe as long as the file is not modified while mapped, which is a documented
+        // requirement of MmapSource
+        match unsafe { Mmap::map(&reader) } {
+            Ok(map) => MmapSource::Mapped { map: map, pos: 0, policy: ErrorPolicy::default() },
+            Err(_)  => MmapSource::Buffered(Source::new(reader)),
+        }
+    }
+
+    /// Returns true if the data is read directly from a memory map.
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        match *self {
+            MmapSource::Mapped { .. } => true,
+            MmapSource::Buffered(_)   => false,
+        }
+    }
+
+    /// Borrows the whole mapped file as a slice, `None` if the file is not mapped.
+    ///
+    /// The slice starts at the beginning of the file, regardless of any data which has been
+    /// parsed using `parse`.
+    #[inline]
+    pub fn as_slice(&self) -> Option<&[u8]> {
+        match *self {
+            MmapSource::Mapped { ref map, .. } => Some(map),
+            MmapSource::Buffered(_)            => None,
+        }
+    }
+
+    /// Returns the number of bytes which have been read but not yet parsed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match *self {
+            MmapSource::Mapped { ref map, pos, .. } => map.len() - pos,
+            MmapSource::Buffered(ref s)             => s.len(),
+        }
+    }
+
+    /// If all of the input has been parsed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            MmapSource::Mapped { .. }   => self.len() == 0,
+            MmapSource::Buffered(ref s) => s.is_empty(),
+        }
+    }
+
+    /// Changes what happens to the input when a parser fails, see `ErrorPolicy`.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy<u8>) {
+        match *self {
+            MmapSource::Mapped { policy: ref mut p, .. } => *p = policy,
+            MmapSource::Buffered(ref mut s)              => s.set_error_policy(policy),
+        }
+    }
+}
+
+impl<'a, R: io::Read> Stream<'a, 'a> for MmapSource<R> {
+    type Item = u8;
+
+    #[inline]
+    fn parse<F, T, E>(&'a mut self, f: F) -> Result<T, StreamError<'a, Self::Item, E>>
+      where F: FnOnce(Input<'a, Self::Item>) -> ParseResult<'a, Self::Item, T, E>,
+            T: 'a,
+            E: 'a {
+        match *self {
+            MmapSource::Mapped { ref map, ref mut pos, policy } => {
+                let start = *pos as u64;
+                let mut s = map[*pos..].into_stream();
+
+                s.set_error_policy(policy);
+
+                let r = s.parse(f);
+
+                *pos = map.len() - s.len();
+
+                // Offsets are relative to the stream created above
+                r.map_err(|e| match e {
+                    StreamError::ParseError(b, e, o) => StreamError::ParseError(b, e, start + o),
+                    StreamError::Incomplete(n, o)    => StreamError::Incomplete(n, start + o),
+                    e                                => e,
+                })
+            },
+            MmapSource::Buffered(ref mut s) => s.parse(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::thread;
+
+    use {Input, ParseResult, Error, parse_only, take, token};
+    use ascii::decimal;
+    use combinators::{iter, sep_by};
+    use buffer::{StreamError, Stream};
+
+    use super::*;
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("chomp-mmap-{}-{}", name, ::std::process::id()));
+
+        File::create(&path).unwrap().write_all(data).unwrap();
+
+        path
+    }
+
+    fn numbers(i: Input<u8>) -> ParseResult<u8, Vec<u32>, Error<u8>> {
+        sep_by(i, decimal, |i| token(i, b','))
+    }
+
+    #[test]
+    fn mapped() {
+        let path  = temp_file("mapped", b"1,22,333");
+        let mut s = MmapSource::open(&path).unwrap();
+
+        assert!(s.is_mapped());
+        assert_eq!(s.len(), 8);
+        assert_eq!(s.as_slice().map(|s| parse_only(numbers, s)), Some(Ok(vec![1, 22, 333])));
+        assert_eq!(s.parse(decimal::<u32>), Ok(1));
+        assert_eq!(s.parse(|i| take(i, 3)), Ok(&b",22"[..]));
+        assert_eq!(s.len(), 4);
+        assert_eq!(s.parse(|i| take(i, 5)), Err(StreamError::Incomplete(5, 4)));
+        assert_eq!(s.parse(numbers), Ok(vec![]));
+        assert_eq!(s.parse(|i| take(i, 4)), Ok(&b",333"[..]));
+        assert!(s.is_empty());
+        assert_eq!(s.parse(decimal::<u32>), Err(StreamError::EndOfInput));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn error_policy() {
+        let path  = temp_file("policy", b"1,x;2,");
+        let mut s = MmapSource::open(&path).unwrap();
+
+        s.set_error_policy(ErrorPolicy::rollback());
+
+        assert_eq!(s.parse(|i| decimal::<u32>(i).bind(|i, n| token(i, b',').map(|_| n))), Ok(1));
+
+        match s.parse(decimal::<u32>) {
+            Err(StreamError::ParseError(b, _, 2)) => assert_eq!(b, b"x;2,"),
+            r                                     => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(s.len(), 4);
+
+        s.set_error_policy(ErrorPolicy::skip_to(b';'));
+
+        assert!(s.parse(decimal::<u32>).is_err());
+        assert_eq!(s.parse(decimal::<u32>), Ok(2));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn buffered_error_policy() {
+        use std::os::unix::net::UnixStream;
+
+        let (r, mut w) = UnixStream::pair().unwrap();
+
+        w.write_all(b"x,1").unwrap();
+        drop(w);
+
+        let mut s = MmapSource::new(r);
+
+        s.set_error_policy(ErrorPolicy::skip_to(b','));
+
+        let mut r = Vec::new();
+
+        loop {
+            match s.parse(decimal::<u32>) {
+                Err(StreamError::Retry)      => {},
+                Err(StreamError::EndOfInput) => break,
+                x                            => r.push(x.map_err(|_| ())),
+            }
+        }
+
+        assert_eq!(r, vec![Err(()), Ok(1)]);
+    }
+
+    #[test]
+    fn mapped_iter() {
+        let path = temp_file("iter", b"abc");
+        let s    = MmapSource::open(&path).unwrap();
+
+        let v: Vec<u8> = iter(::primitives::input::new(::primitives::input::END_OF_INPUT, s.as_slice().unwrap()), ::any).collect();
+
+        assert_eq!(v, b"abc");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn buffered_socket() {
+        use std::os::unix::net::UnixStream;
+
+        // Sockets cannot be mapped
+        let (r, mut w) = UnixStream::pair().unwrap();
+
+        let t = thread::spawn(move || w.write_all(b"1,22,333").unwrap());
+
+        let mut s = MmapSource::new(r);
+
+        assert!(!s.is_mapped());
+        assert_eq!(s.as_slice(), None);
+
+        t.join().unwrap();
+
+        assert_eq!(s.parse(numbers), Err(StreamError::Retry));
+        assert_eq!(s.parse(numbers), Ok(vec![1, 22, 333]));
+        assert_eq!(s.parse(numbers), Err(StreamError::EndOfInput));
+    }
+}
@@ -30,17 +30,32 @@
 mod stateful;
 mod buffer;
 mod slice;
+mod feeder;
+#[cfg(feature = "async")]
+mod async_source;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(all(unix, feature = "ring_buffer"))]
+mod ring_buffer;
 
 pub mod data_source;
 
+use std::cmp;
 use std::io;
+use std::hash::{Hash, Hasher};
 
 use {ParseResult, Input};
-use parse::ParseError;
 
-pub use self::slice::SliceStream;
+pub use self::slice::{SliceIter, SliceStream};
 pub use self::data_source::DataSource;
-pub use self::stateful::Source;
+pub use self::feeder::Feeder;
+pub use self::stateful::{Source, SourceIter, Stats};
+#[cfg(feature = "async")]
+pub use self::async_source::{AsyncSource, Fill, Parse};
+#[cfg(feature = "mmap")]
+pub use self::mmap::MmapSource;
+#[cfg(all(unix, feature = "ring_buffer"))]
+pub use self::ring_buffer::RingBuffer;
 pub use self::buffer::{
     Buffer,
     FixedSizeBuffer,
@@ -51,10 +66,12 @@ pub use self::buffer::{
 #[derive(Debug)]
 pub enum StreamError<'a, I, E>
   where I: 'a {
-    /// An error occurred in the parser, the given slice indicates the part which failed.
-    ParseError(&'a [I], E),
-    /// Parser failed to complete with the available data.
-    Incomplete(usize),
+    /// An error occurred in the parser, the given slice indicates the part which failed and the
+    /// number is the offset of the error from the start of the stream.
+    ParseError(&'a [I], E, u64),
+    /// Parser failed to complete with the available data, requiring at least the given number of
+    /// items starting at the given offset from the start of the stream.
+    Incomplete(usize, u64),
     /// An IO-error occurred while attempting to fill the buffer.
     IoError(io::Error),
     /// The last parser completed successfully and there is no more input to parse.
@@ -70,8 +87,8 @@ impl<'a, I, E> PartialEq for StreamError<'a, I, E>
     #[inline]
     fn eq(&self, other: &StreamError<'a, I, E>) -> bool {
         match (self, other) {
-            (&StreamError::ParseError(ref b1, ref e1), &StreamError::ParseError(ref b2, ref e2)) => b1 == b2 && e1 == e2,
-            (&StreamError::Incomplete(n1), &StreamError::Incomplete(n2)) => n1 == n2,
+            (&StreamError::ParseError(ref b1, ref e1, o1), &StreamError::ParseError(ref b2, ref e2, o2)) => b1 == b2 && e1 == e2 && o1 == o2,
+            (&StreamError::Incomplete(n1, o1), &StreamError::Incomplete(n2, o2)) => n1 == n2 && o1 == o2,
             (&StreamError::EndOfInput, &StreamError::EndOfInput) => true,
             (&StreamError::Retry, &StreamError::Retry) => true,
             _ => false,
@@ -79,12 +96,158 @@ impl<'a, I, E> PartialEq for StreamError<'a, I, E>
     }
 }
 
-impl<'a, I, E> From<ParseError<'a, I, E>> for StreamError<'a, I, E>
-  where I: 'a {
-    fn from(e: ParseError<'a, I, E>) -> Self {
-        match e {
-            ParseError::Error(b, e)   => StreamError::ParseError(b, e),
-            ParseError::Incomplete(n) => StreamError::Incomplete(n),
+/// Determines what a `Stream` does with its buffer when a parser fails with an error.
+///
+/// Set using `Source::set_error_policy` and `SliceStream::set_error_policy`, the default is
+/// `ErrorPolicy::consume_to_error()`.
+///
+/// ```
+/// use chomp::{Input, ParseResult, Error, take_while1, token};
+/// use chomp::ascii::{decimal, is_digit};
+/// use chomp::buffer::{ErrorPolicy, SliceStream, Stream};
+///
+/// fn record(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+///     decimal(i).bind(|i, n| token(i, b'\n').map(|_| n))
+/// }
+///
+/// let mut s = SliceStream::new(b"1\n2x\n3\n");
+///
+/// s.set_error_policy(ErrorPolicy::skip_to(b'\n'));
+///
+/// assert_eq!(s.parse(record), Ok(1));
+/// assert!(s.parse(record).is_err());
+/// // The rest of the broken record was skipped
+/// assert_eq!(s.parse(record), Ok(3));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ErrorPolicy<I>(Policy<I>);
+
+#[derive(Clone, Copy, Debug)]
+enum Policy<I> {
+    ConsumeToError,
+    Rollback,
+    /// Delimiter and its equality
+    SkipTo(I, fn(&I, &I) -> bool),
+    Custom(fn(&[I], usize) -> usize),
+}
+
+impl<I> ErrorPolicy<I> {
+    /// Consumes the input up to the position where the error occurred, the default.
+    #[inline]
+    pub fn consume_to_error() -> Self {
+        ErrorPolicy(Policy::ConsumeToError)
+    }
+
+    /// Leaves the buffer untouched, the next parser will start where the failed parser started.
+    #[inline]
+    pub fn rollback() -> Self {
+        ErrorPolicy(Policy::Rollback)
+    }
+
+    /// Skips the input up to and including the first ``delimiter`` at or after the position where
+    /// the error occurred.
+    ///
+    /// If the delimiter is not present the rest of the input is skipped, `Source` will keep
+    /// skipping data while refilling until it finds the delimiter.
+    #[inline]
+    pub fn skip_to(delimiter: I) -> Self
+      where I: PartialEq {
+        ErrorPolicy(Policy::SkipTo(delimiter, PartialEq::eq))
+    }
+
+    /// Calls ``f`` with the buffer, starting where the failed parser started, and the offset of
+    /// the error in it, consuming the number of items it returns.
+    ///
+    /// Values larger than the length of the buffer will consume all of it.
+    #[inline]
+    pub fn custom(f: fn(&[I], usize) -> usize) -> Self {
+        ErrorPolicy(Policy::Custom(f))
+    }
+
+    /// Returns the number of items to consume from ``buffer`` after an error at offset ``pos``,
+    /// `None` if all of it should be consumed and skipping continued on new data.
+    #[inline]
+    fn on_error(&self, buffer: &[I], pos: usize) -> Option<usize> {
+        match self.0 {
+            Policy::ConsumeToError => Some(pos),
+            Policy::Rollback       => Some(0),
+            Policy::SkipTo(..)     => self.skip(&buffer[pos..]).map(|n| pos + n),
+            Policy::Custom(f)      => Some(cmp::min(f(buffer, pos), buffer.len())),
+        }
+    }
+
+    /// Returns the number of items up to and including the delimiter in ``buffer``, `None` if it
+    /// is not present.
+    #[inline]
+    fn skip(&self, buffer: &[I]) -> Option<usize> {
+        match self.0 {
+            Policy::SkipTo(ref d, eq) => buffer.iter().position(|i| eq(i, d)).map(|n| n + 1),
+            _                         => Some(0),
+        }
+    }
+}
+
+impl<I: PartialEq> PartialEq for Policy<I> {
+    fn eq(&self, other: &Policy<I>) -> bool {
+        match (self, other) {
+            (&Policy::ConsumeToError, &Policy::ConsumeToError) => true,
+            (&Policy::Rollback, &Policy::Rollback) => true,
+            // The equality function is determined by the type of the delimiter
+            (&Policy::SkipTo(ref d1, _), &Policy::SkipTo(ref d2, _)) => d1 == d2,
+            (&Policy::Custom(f1), &Policy::Custom(f2)) => f1 as usize == f2 as usize,
+            _ => false,
+        }
+    }
+}
+
+impl<I: Eq> Eq for Policy<I> {}
+
+impl<I: Hash> Hash for Policy<I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Policy::ConsumeToError   => 0.hash(state),
+            Policy::Rollback         => 1.hash(state),
+            Policy::SkipTo(ref d, _) => {
+                2.hash(state);
+                d.hash(state)
+            },
+            Policy::Custom(f)        => {
+                3.hash(state);
+                f.hash(state)
+            },
+        }
+    }
+}
+
+impl<I> Default for ErrorPolicy<I> {
+    #[inline]
+    fn default() -> Self {
+        Self::consume_to_error()
+    }
+}
+
+/// Error yielded by `SourceIter` and `Feeder`, an owned version of `StreamError`.
+///
+/// `SourceIter` stops after it has yielded an error.
+#[derive(Debug)]
+pub enum IterError<E> {
+    /// An error occurred in the parser at the given offset from the start of the stream.
+    ParseError(E, u64),
+    /// Parser failed to complete with the available data, requiring at least the given number of
+    /// items starting at the given offset from the start of the stream.
+    Incomplete(usize, u64),
+    /// An IO-error occurred while attempting to fill the buffer.
+    IoError(io::Error),
+}
+
+impl<E> PartialEq for IterError<E>
+  where E: PartialEq {
+    #[inline]
+    fn eq(&self, other: &IterError<E>) -> bool {
+        match (self, other) {
+            (&IterError::ParseError(ref e1, o1), &IterError::ParseError(ref e2, o2)) => e1 == e2 && o1 == o2,
+            (&IterError::Incomplete(n1, o1), &IterError::Incomplete(n2, o2)) => n1 == n2 && o1 == o2,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,349 @@
+//! Ring buffer mapping the same memory twice to present a contiguous slice.
+
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::ops;
+use std::ptr;
+use std::slice;
+
+use libc;
+
+use buffer::{Buffer, DataSource};
+
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A fixed size ring buffer which never needs to move data to free up space.
+///
+/// The backing memory is mapped twice in a row in virtual memory, so any run of at most
+/// `capacity()` items starting anywhere in the first mapping is a contiguous slice, even if it
+/// wraps around the end of the ring. Consuming data immediately frees the space for the next
+/// `fill` without the copy `FixedSizeBuffer::request_space` performs.
+///
+/// The capacity is rounded up to a multiple of the page size. Only available on unix platforms.
+///
+/// ```
+/// use chomp::take;
+/// use chomp::buffer::{Buffer, RingBuffer, Source, Stream};
+///
+/// let b = RingBuffer::with_size(100).unwrap();
+///
+/// // Rounded up to a full page
+/// assert!(b.capacity() >= 100);
+///
+/// let mut s = Source::from_read(&b"data"[..], b);
+///
+/// assert_eq!(s.parse(|i| take(i, 2)), Ok(&b"da"[..]));
+/// ```
+pub struct RingBuffer<I: Copy> {
+    /// Start of the first of the two mappings.
+    ptr:       *mut I,
+    /// Number of items in one mapping.
+    capacity:  usize,
+    /// Index of the first unconsumed item, always less than `capacity` after a `fill`.
+    used:      Cell<usize>,
+    /// Index one past the last populated item, `used + len()`.
+    populated: usize,
+}
+
+impl<I: Copy> RingBuffer<I> {
+    /// Creates a ring buffer with the default buffer size.
+    #[inline]
+    pub fn new() -> io::Result<Self> {
+        Self::with_size(DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Creates a ring buffer which can hold at least `size` items.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the rounded up size does not fit in the address
+    /// space, or if the memory could not be mapped.
+    pub fn with_size(size: usize) -> io::Result<Self> {
+        assert!(size > 0);
+        assert!(mem::size_of::<I>() > 0);
+
+        let item = mem::size_of::<I>();
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        // Smallest number of items filling an exact number of pages
+        let unit = lcm(page, item) / item;
+        let overflow = || io::Error::new(io::ErrorKind::InvalidInput, "ring buffer size overflows");
+        let capacity = size.div_ceil(unit).checked_mul(unit).ok_or_else(overflow)?;
+        let bytes    = capacity.checked_mul(item).ok_or_else(overflow)?;
+
+        // Both mappings are reserved at once, Drop relies on this not overflowing either
+        if bytes.checked_mul(2).is_none() {
+            return Err(overflow());
+        }
+
+        let ptr = unsafe { map_mirrored(bytes)? };
+
+        Ok(RingBuffer {
+            ptr:       ptr as *mut I,
+            capacity:  capacity,
+            used:      Cell::new(0),
+            populated: 0,
+        })
+    }
+}
+
+impl<I: Copy> Drop for RingBuffer<I> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, 2 * self.capacity * mem::size_of::<I>());
+        }
+    }
+}
+
+// The buffer owns its memory like a Vec
+unsafe impl<I: Copy + Send> Send for RingBuffer<I> {}
+
+impl<I: Copy> fmt::Debug for RingBuffer<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity)
+            .field("used", &self.used.get())
+            .field("populated", &self.populated)
+            .finish()
+    }
+}
+
+impl<I: Copy> ops::Deref for RingBuffer<I> {
+    type Target = [I];
+
+    #[inline]
+    fn deref(&self) -> &[I] {
+        // used < capacity and len() <= capacity, the slice stays within the two mappings
+        unsafe { slice::from_raw_parts(self.ptr.add(self.used.get()), self.len()) }
+    }
+}
+
+impl<I: Copy> ops::DerefMut for RingBuffer<I> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [I] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.add(self.used.get()), self.len()) }
+    }
+}
+
+impl<I: Copy> Buffer<I> for RingBuffer<I> {
+    #[inline]
+    fn fill<S: DataSource<Item=I>>(&mut self, s: &mut S) -> io::Result<usize> {
+        // Move back into the first mapping, the data is mirrored in the same place
+        if self.used.get() >= self.capacity {
+            self.used.set(self.used.get() - self.capacity);
+            self.populated -= self.capacity;
+        }
+
+        let free = self.capacity - self.len();
+        // populated < used + capacity < 2 * capacity, the free space is contiguous
+        let buf  = unsafe { slice::from_raw_parts_mut(self.ptr.add(self.populated), free) };
+
+        s.read(buf).map(|n| {
+            debug_assert!(n <= free);
+
+            self.populated += n;
+
+            n
+        })
+    }
+
+    #[inline]
+    fn request_space(&mut self, _: usize) {
+        // All space not used by unconsumed data is always available
+    }
+
+    #[inline]
+    fn consume(&self, items: usize) {
+        debug_assert!(self.used.get() + items <= self.populated);
+
+        self.used.set(self.used.get() + items)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.populated - self.used.get()
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+
+    a / gcd(a, b) * b
+}
+
+/// Maps a `bytes` large shared memory object twice in a row, returning the start of the first
+/// mapping.
+unsafe fn map_mirrored(bytes: usize) -> io::Result<*mut libc::c_void> {
+    let fd = shared_memory(bytes)?;
+
+    // Reserve the address space for both mappings
+    let base = libc::mmap(ptr::null_mut(), 2 * bytes, libc::PROT_NONE, libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0);
+
+    if base == libc::MAP_FAILED {
+        let err = io::Error::last_os_error();
+
+        libc::close(fd);
+
+        return Err(err);
+    }
+
+    for half in 0..2 {
+        let addr = (base as *mut u8).add(half * bytes) as *mut libc::c_void;
+        let p    = libc::mmap(addr, bytes, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED | libc::MAP_FIXED, fd, 0);
+
+        if p != addr {
+            let err = io::Error::last_os_error();
+
+            libc::munmap(base, 2 * bytes);
+            libc::close(fd);
+
+            return Err(err);
+        }
+    }
+
+    // The mappings keep the memory alive
+    libc::close(fd);
+
+    Ok(base)
+}
+
+/// Creates an anonymous shared memory object of `bytes` size.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn shared_memory(bytes: usize) -> io::Result<libc::c_int> {
+    let fd = libc::memfd_create(b"chomp-ring-buffer\0".as_ptr() as *const libc::c_char, libc::MFD_CLOEXEC);
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if libc::ftruncate(fd, bytes as libc::off_t) != 0 {
+        let err = io::Error::last_os_error();
+
+        libc::close(fd);
+
+        return Err(err);
+    }
+
+    Ok(fd)
+}
+
+/// Creates an anonymous shared memory object of `bytes` size.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+unsafe fn shared_memory(bytes: usize) -> io::Result<libc::c_int> {
+    use std::env;
+    use std::fs::{self, OpenOptions};
+    use std::os::unix::io::IntoRawFd;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = env::temp_dir().join(format!("chomp-ring-buffer-{}-{}", process::id(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+
+    // Only the descriptor is needed
+    fs::remove_file(&path)?;
+    file.set_len(bytes as u64)?;
+
+    Ok(file.into_raw_fd())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use buffer::{Buffer, Source, Stream, StreamError};
+    use buffer::data_source::ReadDataSource;
+    use {take, take_till, token};
+
+    use super::*;
+
+    #[test]
+    fn capacity() {
+        let b = RingBuffer::<u8>::with_size(1).unwrap();
+
+        assert!(b.capacity() >= 1);
+        assert_eq!(b.len(), 0);
+        assert_eq!(&b[..], b"");
+
+        let b = RingBuffer::<[u8; 3]>::with_size(1).unwrap();
+
+        assert_eq!(b.capacity() * 3 % 4096, 0);
+    }
+
+    #[test]
+    fn size_overflow() {
+        assert_eq!(RingBuffer::<u8>::with_size(usize::MAX).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(RingBuffer::<u64>::with_size(usize::MAX / 8).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn size_overflow_rounded() {
+        // Rounding up to whole pages of 3 byte items overflows the byte size
+        let k = (1usize << 52).div_ceil(3);
+
+        assert_eq!(RingBuffer::<[u8; 3]>::with_size(k * 4096).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn wraps() {
+        let mut b    = RingBuffer::<u8>::with_size(1).unwrap();
+        let cap      = b.capacity();
+        let data: Vec<u8> = (0..3 * cap).map(|i| (i % 251) as u8).collect();
+        let mut src  = ReadDataSource::new(io::Cursor::new(&data[..]));
+
+        assert_eq!(b.fill(&mut src).unwrap(), cap);
+        assert_eq!(b.fill(&mut src).unwrap(), 0);
+        assert_eq!(&b[..], &data[..cap]);
+
+        b.consume(cap - 10);
+
+        // Wraps around the end of the first mapping
+        assert_eq!(b.fill(&mut src).unwrap(), cap - 10);
+        assert_eq!(b.len(), cap);
+        assert_eq!(&b[..], &data[cap - 10..2 * cap - 10]);
+
+        b.consume(cap);
+
+        assert_eq!(b.len(), 0);
+        assert_eq!(b.fill(&mut src).unwrap(), cap);
+        assert_eq!(&b[..], &data[2 * cap - 10..3 * cap - 10]);
+
+        b.consume(5);
+        b.request_space(cap);
+
+        assert_eq!(b.fill(&mut src).unwrap(), 5);
+        assert_eq!(&b[..], &data[2 * cap - 5..3 * cap - 5]);
+    }
+
+    #[test]
+    fn source() {
+        let line = b"a line of text\n";
+        let data: Vec<u8> = line.iter().cloned().cycle().take(line.len() * 1000).collect();
+        let mut s = Source::from_read(&data[..], RingBuffer::with_size(100).unwrap());
+        let mut n = 0;
+
+        loop {
+            match s.parse(|i| take_till(i, |c| c == b'\n').bind(|i, l| token(i, b'\n').map(|_| l))) {
+                Ok(l)                       => {
+                    assert_eq!(l, &line[..line.len() - 1]);
+
+                    n += 1;
+                },
+                Err(StreamError::Retry)      => {},
+                Err(StreamError::EndOfInput) => break,
+                Err(e)                       => panic!("unexpected error: {:?}", e),
+            }
+        }
+
+        assert_eq!(n, 1000);
+        assert_eq!(s.parse(|i| take(i, 1)), Err(StreamError::EndOfInput));
+    }
+}
@@ -2,7 +2,7 @@ use primitives::input;
 use primitives::{State, InputBuffer, IntoInner};
 
 use {Input, ParseResult};
-use buffer::{IntoStream, StreamError, Stream};
+use buffer::{ErrorPolicy, IntoStream, StreamError, Stream};
 
 /// Stream implementation for immutable slices.
 ///
@@ -41,8 +41,9 @@ use buffer::{IntoStream, StreamError, Stream};
 /// ```
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub struct SliceStream<'i, I: 'i> {
-    pos:   usize,
-    slice: &'i [I],
+    pos:    usize,
+    slice:  &'i [I],
+    policy: ErrorPolicy<I>,
 }
 
 impl<'i, I: 'i> SliceStream<'i, I> {
@@ -50,11 +51,18 @@ impl<'i, I: 'i> SliceStream<'i, I> {
     #[inline]
     pub fn new(slice: &'i [I]) -> Self {
         SliceStream {
-            pos:   0,
-            slice: slice,
+            pos:    0,
+            slice:  slice,
+            policy: ErrorPolicy::default(),
         }
     }
 
+    /// Changes what happens to the input when a parser fails, see `ErrorPolicy`.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy<I>) {
+        self.policy = policy
+    }
+
     /// The number of bytes left in the buffer
     #[inline]
     pub fn len(&self) -> usize {
@@ -66,6 +74,90 @@ impl<'i, I: 'i> SliceStream<'i, I> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns the offset of the unparsed data from the start of the slice.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns an iterator applying the parser ``f`` repeatedly until the end of the slice.
+    ///
+    /// The iterator ends at `StreamError::EndOfInput` and after it yields
+    /// `StreamError::Incomplete`. Iteration continues after a parse error if the error policy
+    /// skipped past it, otherwise that error is the last item.
+    ///
+    /// ```
+    /// use chomp::{token, take_while1};
+    /// use chomp::buffer::{ErrorPolicy, SliceStream, StreamError};
+    ///
+    /// let mut s = SliceStream::new(b"ab,c;d,ef,");
+    ///
+    /// s.set_error_policy(ErrorPolicy::skip_to(b','));
+    ///
+    /// let r: Vec<_> = s.iter_parse(|i| take_while1(i, |c| c != b',' && c != b';')
+    ///                                  .bind(|i, w| token(i, b',').map(|_| w))).collect();
+    ///
+    /// assert_eq!(r.len(), 3);
+    /// assert_eq!(r[0], Ok(&b"ab"[..]));
+    /// assert!(matches!(r[1], Err(StreamError::ParseError(b";d,ef,", _, 4))));
+    /// assert_eq!(r[2], Ok(&b"ef"[..]));
+    /// ```
+    #[inline]
+    pub fn iter_parse<'s, F, T, E>(&'s mut self, f: F) -> SliceIter<'s, 'i, I, F>
+      where F: FnMut(Input<'i, I>) -> ParseResult<'i, I, T, E>,
+            T: 'i,
+            E: 'i {
+        SliceIter {
+            stream: self,
+            parser: f,
+            done:   false,
+        }
+    }
+}
+
+/// Iterator created by `SliceStream::iter_parse`.
+#[derive(Debug)]
+pub struct SliceIter<'s, 'i: 's, I: 'i, F> {
+    stream: &'s mut SliceStream<'i, I>,
+    parser: F,
+    /// If the end or an error which cannot be skipped has been reached
+    done:   bool,
+}
+
+impl<'s, 'i, I, F, T, E> Iterator for SliceIter<'s, 'i, I, F>
+  where I: 'i,
+        F: FnMut(Input<'i, I>) -> ParseResult<'i, I, T, E>,
+        T: 'i,
+        E: 'i {
+    type Item = Result<T, StreamError<'i, I, E>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let offset = self.stream.offset();
+
+        match self.stream.parse(&mut self.parser) {
+            Err(StreamError::EndOfInput) => {
+                self.done = true;
+
+                None
+            },
+            r => {
+                self.done = match r {
+                    Ok(_)                              => false,
+                    // Continue if the error policy made progress
+                    Err(StreamError::ParseError(..))   => self.stream.offset() == offset,
+                    Err(_)                             => true,
+                };
+
+                Some(r)
+            },
+        }
+    }
 }
 
 impl<'a, 'i, I: 'i> IntoStream<'a, 'i> for &'i [I] {
@@ -98,13 +190,15 @@ impl<'a, 'i, I: 'i> Stream<'a, 'i> for SliceStream<'i, I> {
                 Ok(data)
             },
             State::Error(remainder, err) => {
-                // TODO: Do something neater with the remainder
-                // TODO: Detail this behaviour, maybe make it configurable
-                self.pos += self.len() - remainder.len();
+                let buffer = &self.slice[self.pos..];
+                let offset = self.pos + buffer.len() - remainder.len();
+
+                // There is no more data to skip through if the delimiter is missing
+                self.pos += self.policy.on_error(buffer, buffer.len() - remainder.len()).unwrap_or(buffer.len());
 
-                Err(StreamError::ParseError(remainder, err))
+                Err(StreamError::ParseError(remainder, err, offset as u64))
             },
-            State::Incomplete(n) => Err(StreamError::Incomplete(n + self.len())),
+            State::Incomplete(n) => Err(StreamError::Incomplete(n + self.len(), self.pos as u64)),
         }
     }
 }
@@ -1,5 +1,6 @@
 use std::io;
 use std::cmp;
+use std::fmt;
 
 use {Input, ParseResult};
 use primitives::input;
@@ -8,7 +9,9 @@ use primitives::{InputBuffer, State, IntoInner};
 use buffer::{
     Buffer,
     DataSource,
+    ErrorPolicy,
     FixedSizeBuffer,
+    IterError,
     StreamError,
     Stream,
 };
@@ -24,9 +27,25 @@ bitflags!{
         const END_OF_INPUT   = 2,
         /// `parse()` should attempt to read more data whenever the `INCOMPLETE` flag is set.
         const AUTOMATIC_FILL = 4,
+        /// The error policy is skipping data until it finds a delimiter.
+        const SKIPPING       = 8,
     }
 }
 
+/// Statistics of the data read and consumed by a `Source`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Stats {
+    /// Total number of items consumed from the buffer, this is the offset of the start of the
+    /// buffer from the start of the stream.
+    pub consumed:     u64,
+    /// Number of times the buffer has been refilled with data from the `DataSource`.
+    pub refills:      u64,
+    /// Total number of items read from the `DataSource`.
+    pub read:         u64,
+    /// The largest number of items the buffer has held at once.
+    pub max_buffered: usize,
+}
+
 /// Manages a buffer and data source pair, enabling efficient parsing from a streaming source.
 #[derive(Debug)]
 pub struct Source<S: DataSource, B: Buffer<S::Item>> {
@@ -38,6 +57,10 @@ pub struct Source<S: DataSource, B: Buffer<S::Item>> {
     request: usize,
     /// Input state, if end has been reached
     state:   ParserState,
+    /// What to do with the buffer when a parser fails
+    policy:  ErrorPolicy<S::Item>,
+    /// Offset and read statistics
+    stats:   Stats,
 }
 
 impl<R: io::Read> Source<ReadDataSource<R>, FixedSizeBuffer<u8>> {
@@ -73,6 +96,8 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
             buffer:  buffer,
             request: 0,
             state:   INCOMPLETE | AUTOMATIC_FILL,
+            policy:  ErrorPolicy::default(),
+            stats:   Stats::default(),
         }
     }
 
@@ -83,6 +108,7 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
 
         let mut buffer = &mut self.buffer;
         let     source = &mut self.source;
+        let     stats  = &mut self.stats;
 
         if buffer.len() < request {
             let diff = request - buffer.len();
@@ -92,9 +118,18 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
             while buffer.len() < request {
                 match try!(buffer.fill(source)) {
                     0 => break,
-                    n => read = read + n,
+                    n => {
+                        read = read + n;
+
+                        stats.read        += n as u64;
+                        stats.max_buffered = cmp::max(stats.max_buffered, buffer.len());
+                    },
                 }
             }
+
+            if read > 0 {
+                stats.refills += 1;
+            }
         }
 
         Ok(read)
@@ -119,6 +154,39 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
         })
     }
 
+    /// Returns an iterator applying the parser ``f`` repeatedly, refilling the buffer whenever it
+    /// runs out of data.
+    ///
+    /// The parser is run again from the start after a refill, so the items and errors it produces
+    /// cannot borrow from the buffer. The iterator ends at `StreamError::EndOfInput` and after it
+    /// yields an IO error or `IterError::Incomplete`. Iteration continues after a parse error if
+    /// the error policy skipped past it, otherwise that error is the last item.
+    ///
+    /// ```
+    /// use chomp::{Input, ParseResult, Error, token};
+    /// use chomp::ascii::decimal;
+    /// use chomp::buffer::{IterError, Source, FixedSizeBuffer};
+    ///
+    /// fn number(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+    ///     decimal(i).bind(|i, n| token(i, b',').map(|_| n))
+    /// }
+    ///
+    /// let mut src = Source::from_read(&b"1,22,333,4"[..], FixedSizeBuffer::with_size(4));
+    ///
+    /// let r: Vec<_> = src.iter_parse(number).collect();
+    ///
+    /// assert_eq!(r, vec![Ok(1), Ok(22), Ok(333), Err(IterError::Incomplete(2, 9))]);
+    /// ```
+    #[inline]
+    pub fn iter_parse<'s, F, T, E>(&'s mut self, f: F) -> SourceIter<'s, S, B, F>
+      where F: FnMut(Input<S::Item>) -> ParseResult<S::Item, T, E> {
+        SourceIter {
+            source: self,
+            parser: f,
+            done:   false,
+        }
+    }
+
     /// Returns the number of bytes left in the buffer which have not yet been parsed.
     #[inline]
     pub fn len(&self) -> usize {
@@ -131,6 +199,18 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
         self.state.contains(END_OF_INPUT) && self.len() == 0
     }
 
+    /// Returns the offset of the start of the buffer from the start of the stream.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.stats.consumed
+    }
+
+    /// Returns statistics of the data read and consumed so far.
+    #[inline]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
     /// Returns the capacity of the underlying buffer.
     ///
     /// This is the maximum number of input items the buffer can store.
@@ -165,6 +245,71 @@ impl<S: DataSource, B: Buffer<S::Item>> Source<S, B> {
             self.state.remove(AUTOMATIC_FILL)
         }
     }
+
+    /// Changes what happens to the buffer when a parser fails, see `ErrorPolicy`.
+    #[inline]
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy<S::Item>) {
+        self.policy = policy
+    }
+}
+
+/// Iterator created by `Source::iter_parse`.
+pub struct SourceIter<'s, S: 's + DataSource, B: 's + Buffer<S::Item>, F> {
+    source: &'s mut Source<S, B>,
+    parser: F,
+    /// If the end or an error which cannot be skipped has been reached
+    done:   bool,
+}
+
+impl<'s, S, B, F> fmt::Debug for SourceIter<'s, S, B, F>
+  where S: DataSource,
+        B: Buffer<S::Item>,
+        Source<S, B>: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SourceIter")
+            .field("source", &self.source)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<'s, S, B, F, T, E> Iterator for SourceIter<'s, S, B, F>
+  where S: DataSource,
+        B: Buffer<S::Item>,
+        F: FnMut(Input<S::Item>) -> ParseResult<S::Item, T, E> {
+    type Item = Result<T, IterError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let offset = self.source.offset();
+
+            let r = match self.source.parse(&mut self.parser) {
+                Ok(t)                              => Ok(t),
+                Err(StreamError::ParseError(_, e, o)) => Err(IterError::ParseError(e, o)),
+                Err(StreamError::Incomplete(n, o))    => Err(IterError::Incomplete(n, o)),
+                Err(StreamError::IoError(e))          => Err(IterError::IoError(e)),
+                Err(StreamError::EndOfInput)          => break,
+                Err(StreamError::Retry)               => match self.source.fill() {
+                    // Filled explicitly in case automatic fill is disabled
+                    Ok(_)  => continue,
+                    Err(e) => Err(IterError::IoError(e)),
+                },
+            };
+
+            self.done = match r {
+                Ok(_)                          => false,
+                // Continue if the error policy made progress
+                Err(IterError::ParseError(..)) => self.source.offset() == offset,
+                Err(_)                         => true,
+            };
+
+            return Some(r);
+        }
+
+        self.done = true;
+
+        None
+    }
 }
 
 impl<S: DataSource<Item=u8>, B: Buffer<u8>> io::Read for Source<S, B> {
@@ -176,6 +321,7 @@ impl<S: DataSource<Item=u8>, B: Buffer<u8>> io::Read for Source<S, B> {
 
         (&self.buffer[..]).read(buf).map(|n| {
             self.buffer.consume(n);
+            self.stats.consumed += n as u64;
 
             n
         })
@@ -194,7 +340,8 @@ impl<S: DataSource<Item=u8>, B: Buffer<u8>> io::BufRead for Source<S, B> {
 
     #[inline]
     fn consume(&mut self, num: usize) {
-        self.buffer.consume(num)
+        self.buffer.consume(num);
+        self.stats.consumed += num as u64;
     }
 }
 
@@ -211,6 +358,29 @@ impl<'a, S: DataSource, B: Buffer<S::Item>> Stream<'a, 'a> for Source<S, B>
             try!(self.fill().map_err(StreamError::IoError));
         }
 
+        if self.state.contains(SKIPPING) {
+            match self.policy.skip(&self.buffer) {
+                Some(n) => {
+                    self.buffer.consume(n);
+                    self.stats.consumed += n as u64;
+                    self.state.remove(SKIPPING);
+                },
+                None    => {
+                    self.stats.consumed += self.buffer.len() as u64;
+                    self.buffer.consume(self.buffer.len());
+
+                    if !self.state.contains(END_OF_INPUT) {
+                        self.request = 0;
+                        self.state.insert(INCOMPLETE);
+
+                        return Err(StreamError::Retry);
+                    }
+
+                    self.state.remove(SKIPPING);
+                },
+            }
+        }
+
         if self.is_empty() {
             return Err(StreamError::EndOfInput);
         }
@@ -219,23 +389,40 @@ impl<'a, S: DataSource, B: Buffer<S::Item>> Stream<'a, 'a> for Source<S, B>
 
         match f(input::new(input_state, &self.buffer)).into_inner() {
             State::Data(remainder, data) => {
+                let n = self.buffer.len() - remainder.buffer().len();
+
                 // TODO: Do something neater with the remainder
-                self.buffer.consume(self.buffer.len() - remainder.buffer().len());
+                self.buffer.consume(n);
+                self.stats.consumed += n as u64;
 
                 Ok(data)
             },
             State::Error(remainder, err) => {
-                // TODO: Do something neater with the remainder
-                // TODO: Detail this behaviour, maybe make it configurable
-                self.buffer.consume(self.buffer.len() - remainder.len());
-
-                Err(StreamError::ParseError(remainder, err))
+                let pos    = self.buffer.len() - remainder.len();
+                let offset = self.stats.consumed + pos as u64;
+                let n      = match self.policy.on_error(&self.buffer, pos) {
+                    Some(n) => n,
+                    None    => {
+                        // Keep skipping on the next call if there is more data
+                        if !self.state.contains(END_OF_INPUT) {
+                            self.state.insert(SKIPPING | INCOMPLETE);
+                            self.request = 0;
+                        }
+
+                        self.buffer.len()
+                    },
+                };
+
+                self.buffer.consume(n);
+                self.stats.consumed += n as u64;
+
+                Err(StreamError::ParseError(remainder, err, offset))
             },
             State::Incomplete(n) => {
                 self.request = self.buffer.len() + n;
 
                 if self.state.contains(END_OF_INPUT) {
-                    Err(StreamError::Incomplete(self.request))
+                    Err(StreamError::Incomplete(self.request, self.stats.consumed))
                 } else {
                     self.state.insert(INCOMPLETE);
 
@@ -249,10 +436,13 @@ impl<'a, S: DataSource, B: Buffer<S::Item>> Stream<'a, 'a> for Source<S, B>
 #[cfg(test)]
 mod test {
     use std::io;
-    use {any, take};
-    use Error;
+    use {any, take, token};
+    use {Error, Input, ParseResult};
+    use ascii::decimal;
     use buffer::{
+        ErrorPolicy,
         FixedSizeBuffer,
+        IterError,
         StreamError,
         Stream,
     };
@@ -400,10 +590,10 @@ mod test {
         assert_eq!(b.parse(|i| { n += 1; take(i, 2).inspect(|_| m += 1) }), Err(StreamError::Retry));
         assert_eq!(n, 2);
         assert_eq!(m, 1);
-        assert_eq!(b.parse(|i| { n += 1; take(i, 2).inspect(|_| m += 1) }), Err(StreamError::Incomplete(2)));
+        assert_eq!(b.parse(|i| { n += 1; take(i, 2).inspect(|_| m += 1) }), Err(StreamError::Incomplete(2, 2)));
         assert_eq!(n, 3);
         assert_eq!(m, 1);
-        assert_eq!(b.parse(|i| { n += 1; take(i, 2).inspect(|_| m += 1) }), Err(StreamError::Incomplete(2)));
+        assert_eq!(b.parse(|i| { n += 1; take(i, 2).inspect(|_| m += 1) }), Err(StreamError::Incomplete(2, 2)));
         assert_eq!(n, 4);
         assert_eq!(m, 1);
     }
@@ -477,4 +667,193 @@ mod test {
         assert_eq!(n, 4);
         assert_eq!(m, 2);
     }
+
+    #[test]
+    fn iter_parse() {
+        let mut b = buf(&b"abcdefg"[..], 2);
+
+        let r: Vec<_> = b.iter_parse(|i| take(i, 2).map(|b| b.to_vec())).collect();
+
+        assert_eq!(r, vec![Ok(b"ab".to_vec()), Ok(b"cd".to_vec()), Ok(b"ef".to_vec()), Err(IterError::<Error<_>>::Incomplete(2, 6))]);
+        assert_eq!(b.iter_parse(any).next(), Some(Ok(b'g')));
+        assert_eq!(b.iter_parse(any).next(), None);
+    }
+
+    #[test]
+    fn iter_parse_no_autofill() {
+        let mut b = buf(&b"abc"[..], 1);
+
+        b.set_autofill(false);
+
+        assert_eq!(b.iter_parse(any).collect::<Vec<_>>(), vec![Ok(b'a'), Ok(b'b'), Ok(b'c')]);
+    }
+
+    #[test]
+    fn iter_parse_error() {
+        let mut b = buf(&b"aab"[..], 2);
+        let mut n = 0;
+
+        let r: Vec<_> = b.iter_parse(|i| { n += 1; token(i, b'a') }).collect();
+
+        assert_eq!(r.len(), 3);
+        assert_eq!(r[0], Ok(b'a'));
+        assert_eq!(r[1], Ok(b'a'));
+        assert!(matches!(r[2], Err(IterError::ParseError(_, 2))));
+        // Retried once after the refill
+        assert_eq!(n, 4);
+        assert_eq!(b.buffer(), b"b");
+    }
+
+    #[test]
+    fn iter_parse_skip_to() {
+        let mut b = buf(&b"1\n2x\n3\n4\n"[..], 4);
+
+        b.set_error_policy(ErrorPolicy::skip_to(b'\n'));
+
+        let r: Vec<_> = b.iter_parse(record).collect();
+
+        assert_eq!(r.len(), 4);
+        assert_eq!(r[0], Ok(1));
+        assert!(matches!(r[1], Err(IterError::ParseError(_, 3))));
+        assert_eq!(r[2], Ok(3));
+        assert_eq!(r[3], Ok(4));
+    }
+
+    #[test]
+    fn iter_parse_io_error() {
+        struct Failing;
+
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("failing"))
+            }
+        }
+
+        let mut b = Source::new(Failing);
+        let mut i = b.iter_parse(any);
+
+        assert!(matches!(i.next(), Some(Err(IterError::IoError(_)))));
+        assert!(i.next().is_none());
+    }
+
+    fn record(i: Input<u8>) -> ParseResult<u8, u32, Error<u8>> {
+        decimal(i).bind(|i, n| token(i, b'\n').map(|_| n))
+    }
+
+    fn parse_record<S: DataSource<Item=u8>, B: Buffer<u8>>(b: &mut Source<S, B>) -> Option<Result<u32, ()>> {
+        loop {
+            match b.parse(record) {
+                Ok(n)                        => return Some(Ok(n)),
+                Err(StreamError::Retry)      => {},
+                Err(StreamError::EndOfInput) => return None,
+                Err(_)                       => return Some(Err(())),
+            }
+        }
+    }
+
+    #[test]
+    fn error_policy_consume_to_error() {
+        let mut b = buf(&b"1x\n2\n"[..], 8);
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(b.buffer(), b"x\n2\n");
+    }
+
+    #[test]
+    fn error_policy_rollback() {
+        let mut b = buf(&b"1x\n2\n"[..], 8);
+
+        b.set_error_policy(ErrorPolicy::rollback());
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(b.buffer(), b"1x\n2\n");
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(b.parse(any), Ok(b'1'));
+    }
+
+    #[test]
+    fn error_policy_skip_to() {
+        let mut b = buf(&b"1x\n2\n3\n"[..], 8);
+
+        b.set_error_policy(ErrorPolicy::skip_to(b'\n'));
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(b.buffer(), b"2\n3\n");
+        assert_eq!(parse_record(&mut b), Some(Ok(2)));
+        assert_eq!(parse_record(&mut b), Some(Ok(3)));
+        assert_eq!(parse_record(&mut b), None);
+    }
+
+    #[test]
+    fn error_policy_skip_to_refill() {
+        // The delimiter is not in the buffer when the error occurs
+        let mut b = buf(&b"1xyz\n2\n"[..], 2);
+
+        b.set_error_policy(ErrorPolicy::skip_to(b'\n'));
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(parse_record(&mut b), Some(Ok(2)));
+        assert_eq!(parse_record(&mut b), None);
+
+        // No delimiter before the end
+        let mut b = buf(&b"1xyz"[..], 2);
+
+        b.set_error_policy(ErrorPolicy::skip_to(b'\n'));
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(parse_record(&mut b), None);
+    }
+
+    #[test]
+    fn error_policy_custom() {
+        fn skip_error(_: &[u8], pos: usize) -> usize {
+            pos + 1
+        }
+
+        let mut b = buf(&b"1x\n2\n"[..], 8);
+
+        b.set_error_policy(ErrorPolicy::custom(skip_error));
+
+        assert_eq!(parse_record(&mut b), Some(Err(())));
+        assert_eq!(b.buffer(), b"\n2\n");
+    }
+
+    #[test]
+    fn stats() {
+        let mut b = buf(&b"abcdefg"[..], 3);
+
+        assert_eq!(b.stats(), Stats::default());
+        assert_eq!(b.parse(|i| take(i, 2)), Ok(&b"ab"[..]));
+        assert_eq!(b.offset(), 2);
+        assert_eq!(b.parse(|i| take(i, 2)), Err(StreamError::Retry));
+        assert_eq!(b.parse(|i| take(i, 2)), Ok(&b"cd"[..]));
+        assert_eq!(b.stats(), Stats { consumed: 4, refills: 2, read: 5, max_buffered: 3 });
+
+        match b.parse(|i| token(i, b'x')) {
+            Err(StreamError::ParseError(r, _, 4)) => assert_eq!(r, b"e"),
+            r                                     => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(b.offset(), 4);
+        assert_eq!(b.parse(|i| take(i, 4)), Err(StreamError::Retry));
+        assert_eq!(b.parse(|i| take(i, 4)), Err(StreamError::Incomplete(4, 4)));
+        assert_eq!(b.stats(), Stats { consumed: 4, refills: 3, read: 7, max_buffered: 3 });
+    }
+
+    #[test]
+    fn stats_read() {
+        use std::io::{BufRead, Read};
+
+        let mut b = buf(&b"abcdefg"[..], 4);
+        let mut v = [0; 3];
+
+        assert_eq!(b.read(&mut v).unwrap(), 3);
+        assert_eq!(b.offset(), 3);
+
+        b.consume(1);
+
+        assert_eq!(b.offset(), 4);
+        assert_eq!(b.parse(any), Ok(b'e'));
+        assert_eq!(b.offset(), 5);
+    }
 }
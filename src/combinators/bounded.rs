@@ -1,9 +1,11 @@
 //! Bounded versions of combinators.
 //!
-//! This module provides bounded versions of `many`, `many_till` and `skip_many`.
+//! This module provides bounded versions of `many`, `many_till`, `skip_many`, `skip_many_till`,
+//! `sep_by` and `sep_end_by`, as well as the non-allocating `fold_many`, `fold_many_till`,
+//! `for_each_many` and `for_each_many_till`.
 //!
-//! The core range types are used to describe a half-open range of successive applications of a
-//! parser. `usize` is used to specify an exact number of iterations:
+//! The core range types are used to describe a half-open or closed range of successive
+//! applications of a parser. `usize` is used to specify an exact number of iterations:
 //!
 //! ```
 //! use chomp::combinators::bounded::many;
@@ -21,7 +23,9 @@ use std::ops::{
     Range,
     RangeFrom,
     RangeFull,
+    RangeInclusive,
     RangeTo,
+    RangeToInclusive,
 };
 use std::cmp::max;
 
@@ -565,14 +569,11 @@ impl BoundedRange for RangeTo<usize> {
             }
 
             => result : T {
-                // Got all occurrences of the parser
-                (s, 0, EndStateTill::EndSuccess)    => s.ret(result),
-                // Did not reach minimum or a failure, propagate
+                // No minimum, so the end can succeed before the maximum has been reached
+                (s, _, EndStateTill::EndSuccess)    => s.ret(result),
+                // A failure, propagate
                 (s, _, EndStateTill::Error(b, e))   => s.replace(b).err(e),
-                (s, _, EndStateTill::Incomplete(n)) => s.incomplete(n),
-                // We cannot reach this since we only run the end test once we have reached the
-                // minimum number of matches
-                (_, _, EndStateTill::EndSuccess)    => unreachable!()
+                (s, _, EndStateTill::Incomplete(n)) => s.incomplete(n)
             }
         }
     }
@@ -698,6 +699,101 @@ impl BoundedRange for usize {
     }
 }
 
+impl BoundedRange for RangeInclusive<usize> {
+    #[inline]
+    fn parse_many<'a, I, T, E, F, U>(self, i: Input<'a, I>, f: F) -> ParseResult<'a, I, T, E>
+      where I: Copy,
+            U: 'a,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+            T: FromIterator<U> {
+        let (start, end) = (*self.start(), *self.end());
+
+        // RangeInclusive does not perform this assertion
+        assert!(start <= end);
+
+        // Closed on both sides, ie. [start, end]
+        match end.checked_add(1) {
+            Some(end) => (start..end).parse_many(i, f),
+            None      => (start..).parse_many(i, f),
+        }
+    }
+
+    #[inline]
+    fn skip_many<'a, I, T, E, F>(self, i: Input<'a, I>, f: F) -> ParseResult<'a, I, (), E>
+      where T: 'a,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        let (start, end) = (*self.start(), *self.end());
+
+        // RangeInclusive does not perform this assertion
+        assert!(start <= end);
+
+        match end.checked_add(1) {
+            Some(end) => (start..end).skip_many(i, f),
+            None      => (start..).skip_many(i, f),
+        }
+    }
+
+    #[inline]
+    fn many_till<'a, I, T, E, R, F, U, N, V>(self, i: Input<'a, I>, p: R, end: F) -> ParseResult<'a, I, T, E>
+      where I: Copy,
+            U: 'a,
+            V: 'a,
+            N: 'a,
+            T: FromIterator<U>,
+            R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
+        let (start, last) = (*self.start(), *self.end());
+
+        // RangeInclusive does not perform this assertion
+        assert!(start <= last);
+
+        match last.checked_add(1) {
+            Some(last) => (start..last).many_till(i, p, end),
+            None       => (start..).many_till(i, p, end),
+        }
+    }
+}
+
+impl BoundedRange for RangeToInclusive<usize> {
+    #[inline]
+    fn parse_many<'a, I, T, E, F, U>(self, i: Input<'a, I>, f: F) -> ParseResult<'a, I, T, E>
+      where I: Copy,
+            U: 'a,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+            T: FromIterator<U> {
+        // Closed on the right side, ie. [0, self.end]
+        match self.end.checked_add(1) {
+            Some(end) => (..end).parse_many(i, f),
+            None      => (..).parse_many(i, f),
+        }
+    }
+
+    #[inline]
+    fn skip_many<'a, I, T, E, F>(self, i: Input<'a, I>, f: F) -> ParseResult<'a, I, (), E>
+      where T: 'a,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        match self.end.checked_add(1) {
+            Some(end) => (..end).skip_many(i, f),
+            None      => (..).skip_many(i, f),
+        }
+    }
+
+    #[inline]
+    fn many_till<'a, I, T, E, R, F, U, N, V>(self, i: Input<'a, I>, p: R, end: F) -> ParseResult<'a, I, T, E>
+      where I: Copy,
+            U: 'a,
+            V: 'a,
+            N: 'a,
+            T: FromIterator<U>,
+            R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+            F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
+        match self.end.checked_add(1) {
+            Some(last) => (..last).many_till(i, p, end),
+            None       => (..).many_till(i, p, end),
+        }
+    }
+}
+
 /// Applies the parser `F` multiple times until it fails or the maximum value of the range has
 /// been reached, collecting the successful values into a `T: FromIterator`.
 ///
@@ -772,6 +868,299 @@ pub fn many_till<'a, I, T, E, R, F, U, N, P, V>(i: Input<'a, I>, r: R, p: P, end
     BoundedRange::many_till(r, i, p, end)
 }
 
+/// Applies the parser `P` multiple times until the parser `F` succeeds, throwing away any
+/// produced value. Consumes the matched part of `F`. If `F` does not succeed within the given
+/// range `R` this combinator will propagate any failure from `P`.
+///
+/// ```
+/// use chomp::combinators::bounded::skip_many_till;
+/// use chomp::{parse_only, any, token};
+///
+/// let r = parse_only(|i| skip_many_till(i, ..4, any, |i| token(i, b'*')).then(any), b"abc*d");
+///
+/// assert_eq!(r, Ok(b'd'));
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will never run `P` more times than the upper bound of the range.
+/// * If the last parser succeeds on the last input item then this combinator is still considered
+///   incomplete unless the parser `F` matches or the lower bound has not been met.
+#[inline]
+pub fn skip_many_till<'a, I, T, E, R, F, U, N, P>(i: Input<'a, I>, r: R, mut p: P, end: F) -> ParseResult<'a, I, (), E>
+  where I: Copy,
+        T: 'a,
+        U: 'a,
+        N: 'a,
+        R: BoundedRange,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E>,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, N> {
+    BoundedRange::many_till(r, i, |i| p(i).map(|_| ()), end)
+}
+
+/// Applies the parser `R` multiple times, separated by the parser `F`, until either fails or the
+/// maximum value of the range has been reached. All matches from `R` will be collected into the
+/// type `T: FromIterator`.
+///
+/// If the separator or parser registers error or incomplete after the minimum number of items
+/// has been read this parser stops and yields the collected value, otherwise the error or
+/// incomplete is propagated. The separator is not consumed unless the following `R` matches.
+///
+/// ```
+/// use chomp::combinators::bounded::sep_by;
+/// use chomp::{parse_only, token};
+/// use chomp::ascii::decimal;
+///
+/// let r: Result<Vec<u8>, _> = parse_only(|i| sep_by(i, 2..4, decimal, |i| token(i, b',')), b"1,2,3,4");
+///
+/// assert_eq!(r, Ok(vec![1, 2, 3]));
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will allocate depending on the `FromIterator` implementation.
+/// * Will never yield more items than the upper bound of the range.
+/// * If the last parser succeeds on the last input item then this parser is still considered
+///   incomplete if the input flag END_OF_INPUT is not set as there might be more data to fill.
+#[inline]
+pub fn sep_by<'a, I, T, E, R, F, U, N, V, P>(i: Input<'a, I>, r: P, mut p: R, mut sep: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        U: 'a,
+        V: 'a,
+        N: 'a,
+        T: FromIterator<U>,
+        E: From<N>,
+        P: BoundedRange,
+        R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
+    // If we have parsed at least one item
+    let mut item = false;
+    // Add sep in front of p if we have read at least one item
+    let parser   = |i| (if item {
+            sep(i).map(|_| ())
+        } else {
+            i.ret(())
+        })
+        .then(&mut p)
+        .inspect(|_| item = true);
+
+    BoundedRange::parse_many(r, i, parser)
+}
+
+/// Applies the parser `R` multiple times, separated and optionally terminated by the parser `F`,
+/// until either fails or the maximum value of the range has been reached. All matches from `R`
+/// will be collected into the type `T: FromIterator`.
+///
+/// Behaves like `sep_by`, but if at least one item was read a trailing separator will also be
+/// consumed if present. Incomplete from the trailing separator is propagated unless the input is
+/// the last slice.
+///
+/// ```
+/// use chomp::combinators::bounded::sep_end_by;
+/// use chomp::{parse_only, token};
+/// use chomp::ascii::decimal;
+///
+/// let p = |i| sep_end_by(i, 1..3, decimal, |i| token(i, b';'));
+///
+/// let r: Result<Vec<u8>, _> = parse_only(&p, b"1;2");
+/// assert_eq!(r, Ok(vec![1, 2]));
+///
+/// let r: Result<(Vec<u8>, _), _> = parse_only(|i| p(i).bind(|i, v| token(i, b'.').map(|_| (v, ()))), b"1;2;.");
+/// assert_eq!(r, Ok((vec![1, 2], ())));
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will allocate depending on the `FromIterator` implementation.
+/// * Will never yield more items than the upper bound of the range.
+/// * If the last parser succeeds on the last input item then this parser is still considered
+///   incomplete if the input flag END_OF_INPUT is not set as there might be more data to fill.
+#[inline]
+pub fn sep_end_by<'a, I, T, E, R, F, U, N, V, P>(i: Input<'a, I>, r: P, mut p: R, mut sep: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        U: 'a,
+        V: 'a,
+        N: 'a,
+        T: FromIterator<U>,
+        E: From<N>,
+        P: BoundedRange,
+        R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
+    // If we have parsed at least one item
+    let mut item = false;
+
+    match sep_by(i.clone(), r, |i| p(i).inspect(|_| item = true), &mut sep).into_inner() {
+        // Only attempt the trailing separator if an item has been read
+        State::Data(b, t)    => if !item {
+            b.ret(t)
+        } else {
+            match sep(b.clone()).into_inner() {
+                State::Data(c, _)    => c.ret(t),
+                State::Error(_, _)   => b.ret(t),
+                State::Incomplete(n) => if b.is_last_slice() {
+                    b.ret(t)
+                } else {
+                    b.incomplete(n)
+                },
+            }
+        },
+        State::Error(b, e)   => i.replace(b).err(e),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
+/// Applies the parser `P` multiple times until it fails or the maximum value of the range has
+/// been reached, folding the successful values into an accumulator starting at `init` using `F`.
+///
+/// Propagates errors if the minimum number of iterations has not been met
+///
+/// ```
+/// use chomp::combinators::bounded::fold_many;
+/// use chomp::{parse_only, any};
+///
+/// let r = parse_only(|i| fold_many(i, 2..4, 0u32, any, |acc, c| acc + c as u32), b"abcd");
+///
+/// assert_eq!(r, Ok(294));
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will not allocate.
+/// * Will never fold more items than the upper bound of the range.
+/// * If the last parser succeeds on the last input item then this parser is still considered
+///   incomplete if the input flag END_OF_INPUT is not set as there might be more data to fill.
+#[inline]
+pub fn fold_many<'a, I, T, E, P, F, U, R>(i: Input<'a, I>, r: R, init: T, mut p: P, mut f: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        U: 'a,
+        R: BoundedRange,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(T, U) -> T {
+    // Only empty while `f` is running
+    let mut acc = Some(init);
+
+    BoundedRange::parse_many(r, i, |i| p(i).map(|u| {
+        let a = acc.take().expect("fold_many: accumulator missing");
+
+        acc = Some(f(a, u));
+    })).map(move |()| acc.expect("fold_many: accumulator missing"))
+}
+
+/// Applies the parser `P` multiple times until the parser `E` succeeds, folding the values
+/// yielded by `P` into an accumulator starting at `init` using `F`. Consumes the matched part of
+/// `E`. If `E` does not succeed within the given range `R` this combinator will propagate any
+/// failure from `P`.
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will not allocate.
+/// * Will never fold more items than the upper bound of the range.
+/// * If the last parser succeeds on the last input item then this combinator is still considered
+///   incomplete unless the parser `E` matches or the lower bound has not been met.
+#[inline]
+pub fn fold_many_till<'a, I, T, E, R, P, G, F, U, N, V>(i: Input<'a, I>, r: R, init: T, mut p: P, end: G, mut f: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        U: 'a,
+        V: 'a,
+        N: 'a,
+        R: BoundedRange,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        G: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N>,
+        F: FnMut(T, U) -> T {
+    // Only empty while `f` is running
+    let mut acc = Some(init);
+
+    BoundedRange::many_till(r, i, |i| p(i).map(|u| {
+        let a = acc.take().expect("fold_many_till: accumulator missing");
+
+        acc = Some(f(a, u));
+    }), end).map(move |()| acc.expect("fold_many_till: accumulator missing"))
+}
+
+/// Applies the parser `P` multiple times until it fails or the maximum value of the range has
+/// been reached, calling `F` with each of the successful values.
+///
+/// Propagates errors if the minimum number of iterations has not been met
+///
+/// ```
+/// use chomp::combinators::bounded::for_each_many;
+/// use chomp::{parse_only, any};
+///
+/// let mut buf = Vec::with_capacity(3);
+///
+/// assert_eq!(parse_only(|i| for_each_many(i, 2..4, any, |c| buf.push(c)), b"abcd"), Ok(()));
+/// assert_eq!(buf, vec![b'a', b'b', b'c']);
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will not allocate.
+/// * Will never yield more items than the upper bound of the range.
+/// * `F` is also called for values yielded before an error which is propagated.
+/// * If the last parser succeeds on the last input item then this parser is still considered
+///   incomplete if the input flag END_OF_INPUT is not set as there might be more data to fill.
+#[inline]
+pub fn for_each_many<'a, I, E, P, F, U, R>(i: Input<'a, I>, r: R, mut p: P, mut f: F) -> ParseResult<'a, I, (), E>
+  where I: Copy,
+        U: 'a,
+        R: BoundedRange,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(U) {
+    BoundedRange::parse_many(r, i, |i| p(i).map(&mut f))
+}
+
+/// Applies the parser `P` multiple times until the parser `E` succeeds, calling `F` with each of
+/// the values yielded by `P`. Consumes the matched part of `E`. If `E` does not succeed within
+/// the given range `R` this combinator will propagate any failure from `P`.
+///
+/// # Panics
+///
+/// Will panic if the end of the range is smaller than the start of the range.
+///
+/// # Notes
+///
+/// * Will not allocate.
+/// * Will never yield more items than the upper bound of the range.
+/// * `F` is also called for values yielded before an error which is propagated.
+/// * If the last parser succeeds on the last input item then this combinator is still considered
+///   incomplete unless the parser `E` matches or the lower bound has not been met.
+#[inline]
+pub fn for_each_many_till<'a, I, E, R, P, G, F, U, N, V>(i: Input<'a, I>, r: R, mut p: P, end: G, mut f: F) -> ParseResult<'a, I, (), E>
+  where I: Copy,
+        U: 'a,
+        V: 'a,
+        N: 'a,
+        R: BoundedRange,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        G: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N>,
+        F: FnMut(U) {
+    BoundedRange::many_till(r, i, |i| p(i).map(&mut f), end)
+}
+
 #[cfg(test)]
 mod test {
     use ParseResult;
@@ -780,9 +1169,16 @@ mod test {
     use primitives::{IntoInner, State};
 
     use super::{
+        fold_many,
+        fold_many_till,
+        for_each_many,
+        for_each_many_till,
         many,
         many_till,
+        sep_by,
+        sep_end_by,
         skip_many,
+        skip_many_till,
     };
 
     #[test]
@@ -1235,6 +1631,162 @@ mod test {
         assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b"ab"), ()));
     }
 
+    #[test]
+    fn many_range_inclusive() {
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"a"), 0..=0, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"a"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aaab"), 2..=3, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"b"), vec![b'a', b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aaaab"), 2..=3, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"ab"), vec![b'a', b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aa"), 2..=3, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Incomplete(1));
+        let r: ParseResult<_, Vec<_>, _> = many(new(END_OF_INPUT, b"aa"), 2..=3, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b""), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many(new(END_OF_INPUT, b"ab"), 2..=3, |i| token(i, b'a').map_err(|_| "token_err"));
+        assert_eq!(r.into_inner(), State::Error(b"b", "token_err"));
+        let r: ParseResult<_, Vec<_>, _> = many(new(END_OF_INPUT, b"aab"), 2..=usize::MAX, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b"b"), vec![b'a', b'a']));
+
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aab"), ..=0, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"aab"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aaab"), ..=2, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"ab"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"b"), ..=2, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"b"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = many(new(END_OF_INPUT, b"aab"), ..=usize::MAX, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b"b"), vec![b'a', b'a']));
+    }
+
+    #[test]
+    fn skip_range_inclusive() {
+        assert_eq!(skip_many(new(DEFAULT, b"aaaab"), 2..=3, |i| token(i, b'a')).into_inner(), State::Data(new(DEFAULT, b"ab"), ()));
+        assert_eq!(skip_many(new(DEFAULT, b"aa"), 2..=3, |i| token(i, b'a')).into_inner(), State::Incomplete(1));
+        assert_eq!(skip_many(new(END_OF_INPUT, b"ab"), 2..=3, |i| token(i, b'a').map_err(|_| "token_err")).into_inner(), State::Error(b"b", "token_err"));
+        assert_eq!(skip_many(new(DEFAULT, b"aaab"), ..=2, |i| token(i, b'a')).into_inner(), State::Data(new(DEFAULT, b"ab"), ()));
+        assert_eq!(skip_many(new(END_OF_INPUT, b"aaa"), ..=usize::MAX, |i| token(i, b'a')).into_inner(), State::Data(new(END_OF_INPUT, b""), ()));
+    }
+
+    #[test]
+    fn many_till_range_inclusive() {
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"aab"), 1..=2, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b""), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"aaab"), 1..=2, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Incomplete(1));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"b"), 1..=2, |i| token(i, b'a').map_err(|_| "token_err"), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Error(b"b", "token_err"));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"bc"), ..=2, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"aabc"), ..=2, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), vec![b'a', b'a']));
+    }
+
+    #[test]
+    fn many_till_range_to_end_before_max() {
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"bc"), ..3, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"abc"), ..3, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), vec![b'a']));
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"aabc"), ..3, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), vec![b'a', b'a']));
+    }
+
+    #[test]
+    fn skip_many_till_range() {
+        assert_eq!(skip_many_till(new(DEFAULT, b"aab"), .., |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Data(new(DEFAULT, b""), ()));
+        assert_eq!(skip_many_till(new(DEFAULT, b"aab"), 2..4, |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Data(new(DEFAULT, b""), ()));
+        assert_eq!(skip_many_till(new(DEFAULT, b"ab"), 2..4, |i| token(i, b'a').map_err(|_| "token_err"), |i| token(i, b'b')).into_inner(), State::Error(b"b", "token_err"));
+        assert_eq!(skip_many_till(new(DEFAULT, b"aa"), 2..4, |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Incomplete(1));
+        assert_eq!(skip_many_till(new(DEFAULT, b"aab"), 2, |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Data(new(DEFAULT, b""), ()));
+        assert_eq!(skip_many_till(new(DEFAULT, b"abc"), ..=1, |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Data(new(DEFAULT, b"c"), ()));
+        assert_eq!(skip_many_till(new(DEFAULT, b"abc"), 1.., |i| token(i, b'a'), |i| token(i, b'b')).into_inner(), State::Data(new(DEFAULT, b"c"), ()));
+    }
+
+    #[test]
+    fn sep_by_range() {
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a,a,a;"), 2..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",a;"), vec![b'a', b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a;"), 2..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b";"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a,;"), 2..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",;"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a;"), 2..4, |i| token(i, b'a').map_err(|_| "token_err"), |i| token(i, b',').map_err(|_| "sep_err"));
+        assert_eq!(r.into_inner(), State::Error(b";", "sep_err"));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,"), 2..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Incomplete(1));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(END_OF_INPUT, b"a,a"), 2..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b""), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b";"), .., |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b";"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a,a;"), 2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",a;"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a,a;"), 1..=2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",a;"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_by(new(DEFAULT, b"a,a,a;"), ..=1, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",a,a;"), vec![b'a']));
+    }
+
+    #[test]
+    fn sep_end_by_range() {
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b"a,a;"), 1..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b";"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b"a,a,;"), 1..4, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b";"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b"a,a,a,a;"), 1..=2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"a,a;"), vec![b'a', b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b",;"), .., |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b",;"), vec![]));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b";"), 1.., |i| token(i, b'a').map_err(|_| "token_err"), |i| token(i, b',').map_err(|_| "sep_err"));
+        assert_eq!(r.into_inner(), State::Error(b";", "token_err"));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b"a,a"), ..2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"a"), vec![b'a']));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(DEFAULT, b"a"), ..2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Incomplete(1));
+        let r: ParseResult<_, Vec<_>, _> = sep_end_by(new(END_OF_INPUT, b"a"), ..2, |i| token(i, b'a'), |i| token(i, b','));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b""), vec![b'a']));
+    }
+
+    #[test]
+    fn fold_many_range() {
+        assert_eq!(fold_many(new(DEFAULT, b"aaab"), 2..4, 0, |i| token(i, b'a'), |n, _| n + 1).into_inner(), State::Data(new(DEFAULT, b"b"), 3));
+        assert_eq!(fold_many(new(DEFAULT, b"aaaab"), 2..4, 0, |i| token(i, b'a'), |n, _| n + 1).into_inner(), State::Data(new(DEFAULT, b"ab"), 3));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"ab"), 2..4, 0, |i| token(i, b'a').map_err(|_| "token_err"), |n, _| n + 1).into_inner(), State::Error(b"b", "token_err"));
+        assert_eq!(fold_many(new(DEFAULT, b"aa"), 2..4, 0, |i| token(i, b'a'), |n, _| n + 1).into_inner(), State::Incomplete(1));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"aa"), 2..4, 0, |i| token(i, b'a'), |n, _| n + 1).into_inner(), State::Data(new(END_OF_INPUT, b""), 2));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"aaa"), 2, 0, |i| token(i, b'a'), |n, _| n + 1).into_inner(), State::Data(new(END_OF_INPUT, b"a"), 2));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"123"), .., 0, any, |n, c| n * 10 + (c - b'0') as u32).into_inner(), State::Data(new(END_OF_INPUT, b""), 123));
+    }
+
+    #[test]
+    fn fold_many_till_range() {
+        assert_eq!(fold_many_till(new(DEFAULT, b"abc;d"), 1..4, 0, any, |i| token(i, b';'), |n, _| n + 1).into_inner(), State::Data(new(DEFAULT, b"d"), 3));
+        assert_eq!(fold_many_till(new(DEFAULT, b";;d"), 1..4, 0, any, |i| token(i, b';'), |n, _| n + 1).into_inner(), State::Data(new(DEFAULT, b"d"), 1));
+        assert_eq!(fold_many_till(new(DEFAULT, b"ab;"), 1..4, 0, |i| token(i, b'a').map_err(|_| "token_err"), |i| token(i, b';'), |n, _| n + 1).into_inner(), State::Error(b"b;", "token_err"));
+        assert_eq!(fold_many_till(new(DEFAULT, b"ab"), .., 0, any, |i| token(i, b';'), |n, _| n + 1).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn for_each_many_range() {
+        let mut v = Vec::new();
+        assert_eq!(for_each_many(new(DEFAULT, b"aaaab"), ..3, |i| token(i, b'a'), |c| v.push(c)).into_inner(), State::Data(new(DEFAULT, b"aab"), ()));
+        assert_eq!(v, b"aa");
+
+        let mut v = Vec::new();
+        assert_eq!(for_each_many(new(END_OF_INPUT, b"ab"), 2.., |i| token(i, b'a').map_err(|_| "token_err"), |c| v.push(c)).into_inner(), State::Error(b"b", "token_err"));
+        assert_eq!(v, b"a");
+    }
+
+    #[test]
+    fn for_each_many_till_range() {
+        let mut v = Vec::new();
+        assert_eq!(for_each_many_till(new(DEFAULT, b"abc;d"), .., any, |i| token(i, b';'), |c| v.push(c)).into_inner(), State::Data(new(DEFAULT, b"d"), ()));
+        assert_eq!(v, b"abc");
+
+        let mut v = Vec::new();
+        assert_eq!(for_each_many_till(new(DEFAULT, b";b;d"), 2, any, |i| token(i, b';'), |c| v.push(c)).into_inner(), State::Data(new(DEFAULT, b"d"), ()));
+        assert_eq!(v, b";b");
+    }
+
     #[test]
     #[should_panic]
     fn panic_many_range_lt() {
@@ -1248,6 +1800,26 @@ mod test {
         assert_eq!(skip_many(new(DEFAULT, b"aaaab"), 2..1, |i| token(i, b'a')).into_inner(), State::Data(new(DEFAULT, b"ab"), ()));
     }
 
+    #[test]
+    #[should_panic]
+    fn panic_many_range_inclusive_lt() {
+        let r: ParseResult<_, Vec<_>, _> = many(new(DEFAULT, b"aaaab"), 2..=1, |i| token(i, b'a'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"ab"), vec![b'a', b'a', b'a']));
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_skip_many_range_inclusive_lt() {
+        assert_eq!(skip_many(new(DEFAULT, b"aaaab"), 2..=1, |i| token(i, b'a')).into_inner(), State::Data(new(DEFAULT, b"ab"), ()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_many_till_range_inclusive_lt() {
+        let r: ParseResult<_, Vec<_>, _> = many_till(new(DEFAULT, b"aaaab"), 2..=1, |i| token(i, b'a'), |i| token(i, b'b'));
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"ab"), vec![b'a', b'a', b'a']));
+    }
+
     #[test]
     #[should_panic]
     fn panic_many_till_range_lt() {
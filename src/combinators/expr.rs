@@ -0,0 +1,492 @@
+//! Operator-precedence expression parsers.
+//!
+//! This module provides a table-driven expression parser (a Pratt parser). The table contains
+//! prefix, infix and postfix operators, each with a binding power; the higher the binding power
+//! the tighter the operator binds to its operands. The `expression` combinator uses the table
+//! together with a parser for the atoms (operands) to build a value, usually an AST.
+//!
+//! ```
+//! use chomp::{parse_only, token};
+//! use chomp::ascii::decimal;
+//! use chomp::combinators::expr::{Assoc, Operators, expression};
+//!
+//! let ops = Operators::new()
+//!     .prefix("-",              3, |i| token(i, b'-'), |a: i64| -a)
+//!     .infix("+", Assoc::Left,  1, |i| token(i, b'+'), |a, b| a + b)
+//!     .infix("-", Assoc::Left,  1, |i| token(i, b'-'), |a, b| a - b)
+//!     .infix("*", Assoc::Left,  2, |i| token(i, b'*'), |a, b| a * b)
+//!     .infix("^", Assoc::Right, 4, |i| token(i, b'^'), |a, b| a.pow(b as u32));
+//!
+//! assert_eq!(parse_only(|i| expression(i, decimal, &ops), b"2+3*-4"), Ok(-10));
+//! assert_eq!(parse_only(|i| expression(i, decimal, &ops), b"2^3^2"), Ok(512));
+//! assert_eq!(parse_only(|i| expression(i, decimal, &ops), b"8-2-1"), Ok(5));
+//! ```
+
+use std::error;
+use std::fmt;
+
+use {Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// Associativity of an infix operator.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Assoc {
+    /// Left-associative, `a - b - c` is parsed as `(a - b) - c`.
+    Left,
+    /// Right-associative, `a ^ b ^ c` is parsed as `a ^ (b ^ c)`.
+    Right,
+    /// Non-associative, `a == b == c` is an error.
+    None,
+}
+
+/// Error produced by `expression`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Error<E> {
+    /// Expected an operand, neither a prefix operator nor the atom parser matched.
+    ///
+    /// Contains the name of the operator which required the operand, `None` if the operand was
+    /// the start of the expression, together with the error from the atom parser.
+    Operand(Option<&'static str>, E),
+    /// A non-associative operator was chained with an operator of the same binding power, the
+    /// error is positioned at the offending operator.
+    ///
+    /// Contains the name of the second operator.
+    NonAssociative(&'static str),
+}
+
+impl<E> fmt::Display for Error<E>
+  where E: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Operand(None, ref e)     => write!(f, "expected operand: {}", e),
+            Error::Operand(Some(op), ref e) => write!(f, "expected operand after {:?}: {}", op, e),
+            Error::NonAssociative(op)       => write!(f, "operator {:?} is non-associative", op),
+        }
+    }
+}
+
+impl<E> error::Error for Error<E>
+  where E: error::Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Operand(_, _)     => "expected an operand, received another token",
+            Error::NonAssociative(_) => "non-associative operator was chained",
+        }
+    }
+}
+
+/// Parser matching an operator, the value and the error are discarded.
+type OpParser<'p, I> = Box<dyn for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, (), ()> + 'p>;
+
+/// Boxes the operator parser `op`, discarding its value and error.
+#[inline]
+fn op_parser<'p, I, U, N, P>(op: P) -> OpParser<'p, I>
+  where U: 'static,
+        N: 'static,
+        P: for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, U, N> + 'p {
+    Box::new(move |i: Input<I>| op(i).map(|_| ()).map_err(|_| ()))
+}
+
+struct Unary<'p, I, T> {
+    name:  &'static str,
+    bp:    u32,
+    op:    OpParser<'p, I>,
+    build: Box<dyn Fn(T) -> T + 'p>,
+}
+
+struct Binary<'p, I, T> {
+    name:  &'static str,
+    assoc: Assoc,
+    bp:    u32,
+    op:    OpParser<'p, I>,
+    build: Box<dyn Fn(T, T) -> T + 'p>,
+}
+
+/// Table of operators used by `expression`.
+///
+/// Operators of the same kind are attempted in the order they were added to the table, an
+/// operator which is a prefix of another operator (eg. `<` and `<=`) needs to be added after the
+/// longer one.
+///
+/// The operator parsers accept input of any lifetime, so a table can be built once and reused
+/// for any number of inputs. Their values cannot borrow from the input, operators matching a
+/// slice need to discard it, eg. `|i| string(i, b"<=").map(|_| ())`.
+///
+/// # Binding power
+///
+/// An operand between two operators is bound by the operator with the higher binding power.
+/// Prefix and postfix operators share binding powers with the infix operators, a prefix `-` with
+/// a binding power of 3 binds tighter than an infix `*` with a binding power of 2, making `-a * b`
+/// parse as `(-a) * b`.
+pub struct Operators<'p, I, T> {
+    prefix:  Vec<Unary<'p, I, T>>,
+    infix:   Vec<Binary<'p, I, T>>,
+    postfix: Vec<Unary<'p, I, T>>,
+}
+
+impl<'p, I, T> Operators<'p, I, T> {
+    /// Creates a new empty operator table.
+    #[inline]
+    pub fn new() -> Self {
+        Operators {
+            prefix:  Vec::new(),
+            infix:   Vec::new(),
+            postfix: Vec::new(),
+        }
+    }
+
+    /// Adds a prefix operator named `name` with the binding power `bp`. `op` matches the operator
+    /// and `build` constructs the value from the operand.
+    #[inline]
+    pub fn prefix<P, U, N, F>(mut self, name: &'static str, bp: u32, op: P, build: F) -> Self
+      where U: 'static,
+            N: 'static,
+            P: for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, U, N> + 'p,
+            F: Fn(T) -> T + 'p {
+        self.prefix.push(Unary {
+            name,
+            bp,
+            op:    op_parser(op),
+            build: Box::new(build),
+        });
+
+        self
+    }
+
+    /// Adds an infix operator named `name` with the associativity `assoc` and the binding power
+    /// `bp`. `op` matches the operator and `build` constructs the value from the left and right
+    /// operands.
+    #[inline]
+    pub fn infix<P, U, N, F>(mut self, name: &'static str, assoc: Assoc, bp: u32, op: P, build: F) -> Self
+      where U: 'static,
+            N: 'static,
+            P: for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, U, N> + 'p,
+            F: Fn(T, T) -> T + 'p {
+        self.infix.push(Binary {
+            name,
+            assoc,
+            bp,
+            op:    op_parser(op),
+            build: Box::new(build),
+        });
+
+        self
+    }
+
+    /// Adds a postfix operator named `name` with the binding power `bp`. `op` matches the
+    /// operator and `build` constructs the value from the operand.
+    #[inline]
+    pub fn postfix<P, U, N, F>(mut self, name: &'static str, bp: u32, op: P, build: F) -> Self
+      where U: 'static,
+            N: 'static,
+            P: for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, U, N> + 'p,
+            F: Fn(T) -> T + 'p {
+        self.postfix.push(Unary {
+            name,
+            bp,
+            op:    op_parser(op),
+            build: Box::new(build),
+        });
+
+        self
+    }
+}
+
+impl<'p, I, T> Default for Operators<'p, I, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'p, I, T> fmt::Debug for Operators<'p, I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let prefix:  Vec<_> = self.prefix.iter().map(|o| (o.name, o.bp)).collect();
+        let infix:   Vec<_> = self.infix.iter().map(|o| (o.name, o.assoc, o.bp)).collect();
+        let postfix: Vec<_> = self.postfix.iter().map(|o| (o.name, o.bp)).collect();
+
+        f.debug_struct("Operators")
+            .field("prefix", &prefix)
+            .field("infix", &infix)
+            .field("postfix", &postfix)
+            .finish()
+    }
+}
+
+/// Parses an expression using the operators in `ops` and `atom` as the operand parser.
+///
+/// Operators which fail to match are backtracked, an operator reporting incomplete is treated as
+/// not matching if the input is the last slice. The expression ends once no operator matches
+/// after an operand.
+///
+/// Fails with `Error::Operand` if an operand is missing and with `Error::NonAssociative` if a
+/// non-associative operator is chained.
+///
+#[cfg_attr(feature = "verbose_error", doc = "
+```
+ use chomp::{Error as ParseErr, ParseError, parse_only, token};
+ use chomp::ascii::decimal;
+ use chomp::combinators::expr::{Assoc, Error, Operators, expression};
+
+ let ops = Operators::new()
+     .infix(\"==\", Assoc::None, 1, |i| token(i, b'='), |a: u32, b| (a == b) as u32)
+     .infix(\"+\",  Assoc::Left, 2, |i| token(i, b'+'), |a, b| a + b);
+
+ assert_eq!(parse_only(|i| expression(i, decimal, &ops), b\"1+2=3\"), Ok(1));
+ assert_eq!(parse_only(|i| expression(i, decimal, &ops), b\"1+)\"),
+            Err(ParseError::Error(b\")\", Error::Operand(Some(\"+\"), ParseErr::Unexpected))));
+ assert_eq!(parse_only(|i| expression(i, decimal, &ops), b\"1=1=1\"),
+            Err(ParseError::Error(b\"=1\", Error::NonAssociative(\"==\"))));
+```
+")]
+#[inline]
+pub fn expression<'p, 'a, I, T, E, F>(i: Input<'a, I>, mut atom: F, ops: &Operators<'p, I, T>) -> ParseResult<'a, I, T, Error<E>>
+  where I: Copy,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    ops.parse_bp(i, 0, None, &mut atom)
+}
+
+/// Result of attempting to match an operator.
+enum Matched<'a, I: 'a> {
+    /// The operator matched, contains the remainder.
+    Yes(Input<'a, I>),
+    /// The operator did not match.
+    No,
+    /// The operator requested more input.
+    Incomplete(usize),
+}
+
+/// Attempts the operator parser `op` on `i`.
+#[inline]
+fn attempt<'a, I: Copy>(i: &Input<'a, I>, op: &OpParser<I>) -> Matched<'a, I> {
+    match op(i.clone()).into_inner() {
+        State::Data(b, _)    => Matched::Yes(b),
+        State::Error(_, _)   => Matched::No,
+        State::Incomplete(n) => if i.is_last_slice() {
+            Matched::No
+        } else {
+            Matched::Incomplete(n)
+        },
+    }
+}
+
+/// Binding powers to the left and right of an infix operator.
+#[inline]
+fn infix_bp(assoc: Assoc, bp: u32) -> (u32, u32) {
+    match assoc {
+        Assoc::Left | Assoc::None => (2 * bp,     2 * bp + 1),
+        Assoc::Right              => (2 * bp + 1, 2 * bp),
+    }
+}
+
+impl<'p, I: Copy, T> Operators<'p, I, T> {
+    /// Parses an operand, either a prefix operator followed by its operand or an atom.
+    fn operand<'a, E, F>(&self, i: Input<'a, I>, after: Option<&'static str>, atom: &mut F) -> ParseResult<'a, I, T, Error<E>>
+      where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        for op in &self.prefix {
+            match attempt(&i, &op.op) {
+                Matched::Yes(b)        => return self.parse_bp(b, 2 * op.bp, Some(op.name), atom).map(|t| (op.build)(t)),
+                Matched::No            => (),
+                Matched::Incomplete(n) => return i.incomplete(n),
+            }
+        }
+
+        match atom(i.clone()).into_inner() {
+            State::Data(b, t)    => b.ret(t),
+            State::Error(b, e)   => i.replace(b).err(Error::Operand(after, e)),
+            State::Incomplete(n) => i.incomplete(n),
+        }
+    }
+
+    /// Parses an expression containing only operators binding at least as tight as `min_bp`.
+    fn parse_bp<'a, E, F>(&self, i: Input<'a, I>, min_bp: u32, after: Option<&'static str>, atom: &mut F) -> ParseResult<'a, I, T, Error<E>>
+      where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        let (mut i, mut lhs) = match self.operand(i.clone(), after, atom).into_inner() {
+            State::Data(b, t)    => (b, t),
+            State::Error(b, e)   => return i.replace(b).err(e),
+            State::Incomplete(n) => return i.incomplete(n),
+        };
+
+        // Binding power of the non-associative operator which produced `lhs`, if any
+        let mut non_assoc = None;
+
+        'ops: loop {
+            for op in &self.postfix {
+                if 2 * op.bp < min_bp {
+                    continue;
+                }
+
+                match attempt(&i, &op.op) {
+                    Matched::Yes(b)        => {
+                        i         = b;
+                        lhs       = (op.build)(lhs);
+                        non_assoc = None;
+
+                        continue 'ops;
+                    },
+                    Matched::No            => (),
+                    Matched::Incomplete(n) => return i.incomplete(n),
+                }
+            }
+
+            for op in &self.infix {
+                let (l_bp, r_bp) = infix_bp(op.assoc, op.bp);
+
+                if l_bp < min_bp {
+                    continue;
+                }
+
+                match attempt(&i, &op.op) {
+                    Matched::Yes(b)        => {
+                        if op.assoc == Assoc::None && non_assoc == Some(op.bp) {
+                            return i.err(Error::NonAssociative(op.name));
+                        }
+
+                        match self.parse_bp(b, r_bp, Some(op.name), atom).into_inner() {
+                            State::Data(b, rhs)  => {
+                                i   = b;
+                                lhs = (op.build)(lhs, rhs);
+                            },
+                            State::Error(b, e)   => return i.replace(b).err(e),
+                            State::Incomplete(n) => return i.incomplete(n),
+                        }
+
+                        non_assoc = if op.assoc == Assoc::None { Some(op.bp) } else { None };
+
+                        continue 'ops;
+                    },
+                    Matched::No            => (),
+                    Matched::Incomplete(n) => return i.incomplete(n),
+                }
+            }
+
+            break;
+        }
+
+        i.ret(lhs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::{IntoInner, State};
+    use parsers::{any, string, token, Error as ParseError};
+    use ascii::decimal;
+
+    use super::{Assoc, Error, Operators, expression};
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum Ast {
+        Num(u32),
+        Neg(Box<Ast>),
+        Fact(Box<Ast>),
+        Bin(char, Box<Ast>, Box<Ast>),
+    }
+
+    use self::Ast::*;
+
+    fn bin(c: char, a: Ast, b: Ast) -> Ast {
+        Bin(c, Box::new(a), Box::new(b))
+    }
+
+    fn ops() -> Operators<'static, u8, Ast> {
+        Operators::new()
+            .prefix("-",              5, |i| token(i, b'-'), |a| Neg(Box::new(a)))
+            .postfix("!",             6, |i| token(i, b'!'), |a| Fact(Box::new(a)))
+            .infix("=", Assoc::None,  1, |i| token(i, b'='), |a, b| bin('=', a, b))
+            .infix("+", Assoc::Left,  2, |i| token(i, b'+'), |a, b| bin('+', a, b))
+            .infix("-", Assoc::Left,  2, |i| token(i, b'-'), |a, b| bin('-', a, b))
+            .infix("*", Assoc::Left,  3, |i| token(i, b'*'), |a, b| bin('*', a, b))
+            .infix("^", Assoc::Right, 4, |i| token(i, b'^'), |a, b| bin('^', a, b))
+    }
+
+    fn num(n: u32) -> Ast {
+        Num(n)
+    }
+
+    #[test]
+    fn precedence() {
+        let t = ops();
+        let p = |i| expression(i, |i| decimal(i).map(Num), &t);
+
+        assert_eq!(p(new(END_OF_INPUT, b"1")).into_inner(), State::Data(new(END_OF_INPUT, b""), num(1)));
+        assert_eq!(p(new(END_OF_INPUT, b"1+2*3")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('+', num(1), bin('*', num(2), num(3)))));
+        assert_eq!(p(new(END_OF_INPUT, b"1*2+3")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('+', bin('*', num(1), num(2)), num(3))));
+        assert_eq!(p(new(END_OF_INPUT, b"1-2-3")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('-', bin('-', num(1), num(2)), num(3))));
+        assert_eq!(p(new(END_OF_INPUT, b"1^2^3")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('^', num(1), bin('^', num(2), num(3)))));
+        assert_eq!(p(new(END_OF_INPUT, b"1+2=3")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('=', bin('+', num(1), num(2)), num(3))));
+    }
+
+    #[test]
+    fn prefix_postfix() {
+        let t = ops();
+        let p = |i| expression(i, |i| decimal(i).map(Num), &t);
+
+        assert_eq!(p(new(END_OF_INPUT, b"-1*2")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('*', Neg(Box::new(num(1))), num(2))));
+        assert_eq!(p(new(END_OF_INPUT, b"--1")).into_inner(), State::Data(new(END_OF_INPUT, b""), Neg(Box::new(Neg(Box::new(num(1)))))));
+        assert_eq!(p(new(END_OF_INPUT, b"-1!")).into_inner(), State::Data(new(END_OF_INPUT, b""), Neg(Box::new(Fact(Box::new(num(1)))))));
+        assert_eq!(p(new(END_OF_INPUT, b"1!!+2")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('+', Fact(Box::new(Fact(Box::new(num(1))))), num(2))));
+        assert_eq!(p(new(END_OF_INPUT, b"1-2")).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('-', num(1), num(2))));
+    }
+
+    #[test]
+    fn remainder() {
+        let t = ops();
+        let p = |i| expression(i, |i| decimal(i).map(Num), &t);
+
+        assert_eq!(p(new(END_OF_INPUT, b"1+2 ")).into_inner(), State::Data(new(END_OF_INPUT, b" "), bin('+', num(1), num(2))));
+        assert_eq!(p(new(END_OF_INPUT, b"1)")).into_inner(), State::Data(new(END_OF_INPUT, b")"), num(1)));
+    }
+
+    #[test]
+    fn errors() {
+        let t = ops();
+        let p = |i| expression(i, |i| decimal(i).map(Num), &t);
+
+        assert_eq!(p(new(END_OF_INPUT, b"")).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(END_OF_INPUT, b"a")).into_inner(), State::Error(b"a", Error::Operand(None, ParseError::new())));
+        assert_eq!(p(new(END_OF_INPUT, b"1+")).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(END_OF_INPUT, b"1+)")).into_inner(), State::Error(b")", Error::Operand(Some("+"), ParseError::new())));
+        assert_eq!(p(new(END_OF_INPUT, b"1*-a")).into_inner(), State::Error(b"a", Error::Operand(Some("-"), ParseError::new())));
+        assert_eq!(p(new(END_OF_INPUT, b"1=2=3")).into_inner(), State::Error(b"=3", Error::NonAssociative("=")));
+        assert_eq!(p(new(END_OF_INPUT, b"1=2+3=4")).into_inner(), State::Error(b"=4", Error::NonAssociative("=")));
+    }
+
+    #[test]
+    fn incomplete() {
+        let t = ops();
+        let p = |i| expression(i, |i| decimal(i).map(Num), &t);
+
+        assert_eq!(p(new(DEFAULT, b"")).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(DEFAULT, b"1")).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(DEFAULT, b"1+")).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(DEFAULT, b"1+2 ")).into_inner(), State::Data(new(DEFAULT, b" "), bin('+', num(1), num(2))));
+    }
+
+    #[test]
+    fn reuse() {
+        let t = ops().infix("<=", Assoc::None, 1, |i| string(i, b"<=").map(|_| ()), |a, b| bin('<', a, b));
+
+        {
+            let buf = b"1+2<=3".to_vec();
+
+            assert_eq!(expression(new(END_OF_INPUT, &buf), |i| decimal(i).map(Num), &t).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('<', bin('+', num(1), num(2)), num(3))));
+        }
+
+        {
+            let buf = b"3*-4".to_vec();
+
+            assert_eq!(expression(new(END_OF_INPUT, &buf), |i| decimal(i).map(Num), &t).into_inner(), State::Data(new(END_OF_INPUT, b""), bin('*', num(3), Neg(Box::new(num(4))))));
+        }
+    }
+
+    #[test]
+    fn no_operators() {
+        let t = Operators::new();
+
+        assert_eq!(expression(new(END_OF_INPUT, b"ab"), any, &t).into_inner(), State::Data(new(END_OF_INPUT, b"b"), b'a'));
+        assert_eq!(expression(new(END_OF_INPUT, b""), any, &t).into_inner(), State::Incomplete::<_, _, Error<ParseError<u8>>>(1));
+    }
+}
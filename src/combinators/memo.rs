@@ -0,0 +1,597 @@
+//! Packrat memoization of parsers and support for left-recursive rules.
+//!
+//! Grammars relying heavily on backtracking (eg. through `or`) tend to run the same parser at the
+//! same position multiple times. The `memo` combinator stores the result of a parser in a `Memo`
+//! table keyed by a rule id and the position in the input, reusing the stored result instead of
+//! running the parser again. Memoizing every rule of a grammar results in linear-time (PEG-style
+//! packrat) parsing at the cost of storing all intermediate results.
+//!
+//! The memoization table also enables left-recursive rules through `left_rec`, which would
+//! otherwise recurse forever.
+//!
+//! ```
+//! use chomp::{Input, U8Result, parse_only, or, token};
+//! use chomp::ascii::decimal;
+//! use chomp::combinators::memo::{Memo, memo};
+//!
+//! const NUMBER: usize = 0;
+//!
+//! // Both alternatives start with a number, which is only parsed once
+//! fn expr<'a>(i: Input<'a, u8>, m: &Memo<'a, u8, u32, chomp::Error<u8>>) -> U8Result<'a, u32> {
+//!     or(i,
+//!        |i| memo(i, m, NUMBER, decimal).bind(|i, a| token(i, b'+').then(|i| decimal(i).map(|b: u32| a + b))),
+//!        |i| memo(i, m, NUMBER, decimal))
+//! }
+//!
+//! assert_eq!(parse_only(|i| expr(i, &Memo::new()), b"1+2"), Ok(3));
+//! assert_eq!(parse_only(|i| expr(i, &Memo::new()), b"12;"), Ok(12));
+//! ```
+//!
+//! # Lifetime of the table
+//!
+//! A `Memo` is only valid for a single run of a parser over a single input, it should be created
+//! inside the parser passed to `parse_only` or `buffer::Stream::parse`. The stored results borrow
+//! the input, which prevents a table from being kept across multiple calls to
+//! `buffer::Source::parse`; each `StreamError::Retry` will start with a fresh table.
+//!
+//! As a safeguard the table is also cleared whenever it is used with an input ending at a
+//! different location or with a different end-of-input state than the input it was last used
+//! with, eg. when the same table is reused for multiple `parse_only` calls or when a parser runs
+//! on a sub-slice of the input.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+
+use {Error, Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// A stored parser result.
+#[derive(Clone)]
+enum Entry<'a, I: 'a, T, E> {
+    /// Success, contains the remainder of the input and the value.
+    Data(&'a [I], T),
+    /// Error, contains the position of the error and the error.
+    Error(&'a [I], E),
+    /// Incomplete, contains the number of requested items.
+    Incomplete(usize),
+}
+
+impl<'a, I: Copy, T, E> Entry<'a, I, T, E> {
+    #[inline]
+    fn new(s: State<'a, I, T, E>) -> Self {
+        match s {
+            State::Data(b, d)    => Entry::Data(b.buffer(), d),
+            State::Error(b, e)   => Entry::Error(b, e),
+            State::Incomplete(n) => Entry::Incomplete(n),
+        }
+    }
+
+    #[inline]
+    fn into_result(self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        match self {
+            Entry::Data(b, d)    => i.replace(b).ret(d),
+            Entry::Error(b, e)   => i.replace(b).err(e),
+            Entry::Incomplete(n) => i.incomplete(n),
+        }
+    }
+}
+
+/// Identity of the input a table was populated from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Identity {
+    /// Address of the end of the input buffer.
+    end:  usize,
+    /// If the input was the last slice.
+    last: bool,
+}
+
+impl Identity {
+    #[inline]
+    fn new<'a, I: Copy>(i: &Input<'a, I>) -> Self {
+        let b = i.buffer();
+
+        Identity {
+            end:  b.as_ptr() as usize + mem::size_of_val(b),
+            last: i.is_last_slice(),
+        }
+    }
+}
+
+struct Table<'a, I: 'a, T, E> {
+    ident:   Option<Identity>,
+    /// Results keyed by (rule id, remaining length of the input).
+    entries: HashMap<(usize, usize), Entry<'a, I, T, E>>,
+}
+
+impl<'a, I: Copy, T: Clone, E: Clone> Table<'a, I, T, E> {
+    /// Looks up a stored result, clearing the table if it was populated from another input.
+    #[inline]
+    fn get(&mut self, ident: Identity, key: (usize, usize)) -> Option<Entry<'a, I, T, E>> {
+        if self.ident != Some(ident) {
+            self.ident = Some(ident);
+            self.entries.clear();
+        }
+
+        self.entries.get(&key).cloned()
+    }
+
+    /// Stores a result unless the table has been used with another input in the meantime.
+    #[inline]
+    fn insert(&mut self, ident: Identity, key: (usize, usize), entry: Entry<'a, I, T, E>) {
+        if self.ident == Some(ident) {
+            self.entries.insert(key, entry);
+        }
+    }
+}
+
+/// Memoization table used by `memo`, storing parser results of the type `T` and errors of the
+/// type `E`.
+///
+/// Rules of different value or error types need to use separate tables.
+pub struct Memo<'a, I: 'a, T, E> {
+    table: RefCell<Table<'a, I, T, E>>,
+}
+
+impl<'a, I: 'a, T, E> Memo<'a, I, T, E> {
+    /// Creates a new empty memoization table.
+    #[inline]
+    pub fn new() -> Self {
+        Memo {
+            table: RefCell::new(Table {
+                ident:   None,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Removes all stored results.
+    #[inline]
+    pub fn clear(&self) {
+        let mut t = self.table.borrow_mut();
+
+        t.ident = None;
+        t.entries.clear();
+    }
+
+    /// Returns the number of stored results.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.table.borrow().entries.len()
+    }
+
+    /// Returns true if there are no stored results.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, I: 'a, T, E> Default for Memo<'a, I, T, E> {
+    #[inline]
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+impl<'a, I: 'a, T, E> fmt::Debug for Memo<'a, I, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Memo")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Runs the parser `f` as the rule `rule`, storing the result in the table `m`. If a result for
+/// `rule` at the current position is already stored in `m` it will be returned instead of running
+/// `f`.
+///
+/// All results are stored, including errors and incomplete. `rule` identifies the parser and has
+/// to be unique for each parser using the same table, otherwise results from one parser will be
+/// returned for another.
+///
+/// ```
+/// use std::cell::Cell;
+/// use chomp::{parse_only, or, any, token};
+/// use chomp::combinators::memo::{Memo, memo};
+///
+/// let runs = Cell::new(0);
+/// let m    = Memo::new();
+///
+/// let r = parse_only(|i| or(i,
+///     |i| memo(i, &m, 0, |i| { runs.set(runs.get() + 1); any(i) }).then(|i| token(i, b'b')),
+///     |i| memo(i, &m, 0, |i| { runs.set(runs.get() + 1); any(i) }).then(|i| token(i, b'c'))),
+///     b"ac");
+///
+/// assert_eq!(r, Ok(b'c'));
+/// assert_eq!(runs.get(), 1);
+/// ```
+#[inline]
+pub fn memo<'a, I, T, E, F>(i: Input<'a, I>, m: &Memo<'a, I, T, E>, rule: usize, f: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        T: Clone,
+        E: Clone,
+        F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    let ident = Identity::new(&i);
+    let key   = (rule, i.buffer().len());
+
+    if let Some(e) = m.table.borrow_mut().get(ident, key) {
+        return e.into_result(i);
+    }
+
+    // The table is not borrowed while running the parser since it might recursively use it
+    let entry = Entry::new(f(i.clone()).into_inner());
+
+    // Nested parsers might have used the table with another input
+    m.table.borrow_mut().insert(ident, key, entry.clone());
+
+    entry.into_result(i)
+}
+
+/// Error types usable with left-recursive rules, see `left_rec`.
+pub trait LeftRecursionError {
+    /// Creates the error used as the initial result of a left-recursive rule.
+    ///
+    /// A left-recursive rule will fail with this error if none of its alternatives match without
+    /// recursing.
+    fn left_recursion() -> Self;
+}
+
+impl<I> LeftRecursionError for Error<I> {
+    #[inline]
+    fn left_recursion() -> Self {
+        Error::new()
+    }
+}
+
+/// Runs the left-recursive parser `f` as the rule `rule`, storing the result in the table `m`.
+///
+/// Left recursion is resolved using seed growing: before `f` runs the error
+/// `E::left_recursion()` is stored for `rule` at the current position, which makes the
+/// left-recursive invocation of the rule inside `f` fail and `f` fall back to a non-recursive
+/// alternative. `f` is then run again as long as it consumes more input than the previous run,
+/// each time with the previous result stored for the recursive invocation. The longest match is
+/// the result of the rule.
+///
+/// The left-recursive invocations inside `f` have to use `left_rec` with the same table and rule,
+/// `f` itself is run multiple times and must not have side effects other than on the table.
+/// Incomplete is propagated unless the input is the last slice and a match has been found.
+///
+/// ```
+/// use chomp::{Input, U8Result, parse_only, or, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::memo::{Memo, left_rec};
+///
+/// const EXPR: usize = 0;
+///
+/// // expr = expr '-' number | number
+/// fn expr<'a>(i: Input<'a, u8>, m: &Memo<'a, u8, i32, chomp::Error<u8>>) -> U8Result<'a, i32> {
+///     left_rec(i, m, EXPR, |i| or(i,
+///         |i| expr(i, m).bind(|i, a| token(i, b'-').then(decimal).map(|b: i32| a - b)),
+///         decimal))
+/// }
+///
+/// assert_eq!(parse_only(|i| expr(i, &Memo::new()), b"10-2-3"), Ok(5));
+/// assert_eq!(parse_only(|i| expr(i, &Memo::new()), b"10"), Ok(10));
+/// ```
+///
+/// # Limitations
+///
+/// Only the rule passed to `left_rec` is re-run while growing the seed. For indirect left
+/// recursion (eg. `a = b 'x' | 'y'; b = a 'z'`) the rules other than the one using `left_rec`
+/// must therefore not be memoized.
+#[inline]
+pub fn left_rec<'a, I, T, E, F>(i: Input<'a, I>, m: &Memo<'a, I, T, E>, rule: usize, mut f: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        T: Clone,
+        E: Clone + LeftRecursionError,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    let ident = Identity::new(&i);
+    let key   = (rule, i.buffer().len());
+
+    {
+        let mut t = m.table.borrow_mut();
+
+        if let Some(e) = t.get(ident, key) {
+            return e.into_result(i);
+        }
+
+        // Seed, stops the recursion at the same position
+        t.insert(ident, key, Entry::Error(i.buffer(), E::left_recursion()));
+    }
+
+    let mut best: Entry<'a, I, T, E> = Entry::Error(i.buffer(), E::left_recursion());
+
+    loop {
+        best = match (best, f(i.clone()).into_inner()) {
+            // No progress, the previous result is the longest match
+            (Entry::Data(r, d), State::Data(ref b, _)) if b.buffer().len() >= r.len() => {
+                best = Entry::Data(r, d);
+
+                break;
+            },
+            (_, State::Data(b, d))                     => Entry::Data(b.buffer(), d),
+            (Entry::Data(r, d), State::Error(_, _))    => {
+                best = Entry::Data(r, d);
+
+                break;
+            },
+            (Entry::Data(r, d), State::Incomplete(_)) if i.is_last_slice() => {
+                best = Entry::Data(r, d);
+
+                break;
+            },
+            (_, s)                                     => {
+                best = Entry::new(s);
+
+                break;
+            },
+        };
+
+        m.table.borrow_mut().insert(ident, key, best.clone());
+    }
+
+    m.table.borrow_mut().insert(ident, key, best.clone());
+
+    best.into_result(i)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::{IntoInner, State};
+    use parsers::{any, take, token};
+    use combinators::or;
+
+    use super::{LeftRecursionError, Memo, left_rec, memo};
+
+    #[test]
+    fn reuses_results() {
+        let n = Cell::new(0);
+        let m = Memo::new();
+        let p = |i| memo(i, &m, 0, |i| { n.set(n.get() + 1); take(i, 2) });
+        let b = b"abd";
+
+        assert_eq!(or(new(END_OF_INPUT, b), |i| p(i).then(|i| token(i, b'c')), |i| p(i).then(|i| token(i, b'd'))).into_inner(), State::Data(new(END_OF_INPUT, b""), b'd'));
+        assert_eq!(n.get(), 1);
+        assert_eq!(m.len(), 1);
+
+        // Different position
+        assert_eq!(any(new(END_OF_INPUT, b)).bind(|i, _| p(i)).into_inner(), State::Data(new(END_OF_INPUT, b""), &b"bd"[..]));
+        assert_eq!(n.get(), 2);
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn rules() {
+        let n = Cell::new(0);
+        let m = Memo::new();
+        let b = b"ab";
+
+        assert_eq!(memo(new(END_OF_INPUT, b), &m, 0, |i| { n.set(n.get() + 1); any(i) }).into_inner(), State::Data(new(END_OF_INPUT, b"b"), b'a'));
+        assert_eq!(memo(new(END_OF_INPUT, b), &m, 1, |i| { n.set(n.get() + 1); any(i).then(any) }).into_inner(), State::Data(new(END_OF_INPUT, b""), b'b'));
+        assert_eq!(memo(new(END_OF_INPUT, b), &m, 0, |i| { n.set(n.get() + 1); any(i) }).into_inner(), State::Data(new(END_OF_INPUT, b"b"), b'a'));
+        assert_eq!(memo(new(END_OF_INPUT, b), &m, 1, |i| { n.set(n.get() + 1); any(i) }).into_inner(), State::Data(new(END_OF_INPUT, b""), b'b'));
+        assert_eq!(n.get(), 2);
+    }
+
+    #[test]
+    fn errors_and_incomplete() {
+        let n = Cell::new(0);
+        let m = Memo::new();
+        let b = b"ab";
+
+        let p = |i| memo(i, &m, 0, |i| { n.set(n.get() + 1); any(i).map_err(|_| "any err").then(|i| token(i, b'c').map_err(|_| "token err")) });
+
+        assert_eq!(p(new(END_OF_INPUT, b)).into_inner(), State::Error(&b[1..], "token err"));
+        assert_eq!(p(new(END_OF_INPUT, b)).into_inner(), State::Error(&b[1..], "token err"));
+        assert_eq!(n.get(), 1);
+
+        let b = b"a";
+
+        assert_eq!(p(new(DEFAULT, b)).into_inner(), State::Incomplete(1));
+        assert_eq!(p(new(DEFAULT, b)).into_inner(), State::Incomplete(1));
+        assert_eq!(n.get(), 2);
+    }
+
+    #[test]
+    fn invalidation() {
+        let n = Cell::new(0);
+        let m = Memo::new();
+
+        let p = |i| memo(i, &m, 0, |i| { n.set(n.get() + 1); any(i) });
+
+        let b = b"ab";
+
+        assert_eq!(p(new(DEFAULT, &b[..])).into_inner(), State::Data(new(DEFAULT, b"b"), b'a'));
+        assert_eq!(n.get(), 1);
+
+        // Different end of input state
+        assert_eq!(p(new(END_OF_INPUT, &b[..])).into_inner(), State::Data(new(END_OF_INPUT, b"b"), b'a'));
+        assert_eq!(n.get(), 2);
+        assert_eq!(m.len(), 1);
+
+        // Different end of buffer, but same remaining length
+        assert_eq!(p(new(END_OF_INPUT, &b"cd"[..])).into_inner(), State::Data(new(END_OF_INPUT, b"d"), b'c'));
+        assert_eq!(n.get(), 3);
+
+        // Sub-slice
+        assert_eq!(p(new(END_OF_INPUT, &b[..1])).into_inner(), State::Data(new(END_OF_INPUT, b""), b'a'));
+        assert_eq!(n.get(), 4);
+
+        m.clear();
+
+        assert!(m.is_empty());
+        assert_eq!(p(new(END_OF_INPUT, &b[..1])).into_inner(), State::Data(new(END_OF_INPUT, b""), b'a'));
+        assert_eq!(n.get(), 5);
+    }
+
+    #[test]
+    fn nested() {
+        let n = Cell::new(0);
+        let m = Memo::new();
+
+        fn count<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, usize, ()>, n: &Cell<usize>) -> ::ParseResult<'a, u8, usize, ()> {
+            memo(i, m, 0, |i| {
+                n.set(n.get() + 1);
+
+                or(i, |i| any(i).map_err(|_| ()).then(|i| count(i, m, n)).map(|c| c + 1), |i| i.ret(0))
+            })
+        }
+
+        let b = b"aaa";
+
+        assert_eq!(count(new(END_OF_INPUT, b), &m, &n).into_inner(), State::Data(new(END_OF_INPUT, b""), 3));
+        assert_eq!(n.get(), 4);
+        assert_eq!(m.len(), 4);
+        assert_eq!(count(new(END_OF_INPUT, b), &m, &n).into_inner(), State::Data(new(END_OF_INPUT, b""), 3));
+        assert_eq!(n.get(), 4);
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    enum Err {
+        Seed,
+        Token,
+    }
+
+    impl LeftRecursionError for Err {
+        fn left_recursion() -> Self {
+            Err::Seed
+        }
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    enum Ast {
+        Num(u8),
+        Sub(Box<Ast>, Box<Ast>),
+        Mul(Box<Ast>, Box<Ast>),
+    }
+
+    fn sub(a: Ast, b: Ast) -> Ast {
+        Ast::Sub(Box::new(a), Box::new(b))
+    }
+
+    fn mul(a: Ast, b: Ast) -> Ast {
+        Ast::Mul(Box::new(a), Box::new(b))
+    }
+
+    fn num<'a>(i: ::Input<'a, u8>) -> ::ParseResult<'a, u8, Ast, Err> {
+        any(i).map_err(|_| Err::Token).bind(|i, c| if (b'0'..=b'9').contains(&c) {
+            i.ret(Ast::Num(c - b'0'))
+        } else {
+            i.err(Err::Token)
+        })
+    }
+
+    // expr = expr '-' term | term
+    fn expr<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, Ast, Err>) -> ::ParseResult<'a, u8, Ast, Err> {
+        left_rec(i, m, 0, |i| or(i,
+            |i| expr(i, m).bind(|i, a| token(i, b'-').map_err(|_| Err::Token).then(|i| term(i, m)).map(|b| sub(a, b))),
+            |i| term(i, m)))
+    }
+
+    // term = term '*' num | num
+    fn term<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, Ast, Err>) -> ::ParseResult<'a, u8, Ast, Err> {
+        left_rec(i, m, 1, |i| or(i,
+            |i| term(i, m).bind(|i, a| token(i, b'*').map_err(|_| Err::Token).then(num).map(|b| mul(a, b))),
+            num))
+    }
+
+    #[test]
+    fn left_recursion() {
+        let m = Memo::new();
+        let b = b"1-2*3-4;";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Data(new(END_OF_INPUT, b";"), sub(sub(Ast::Num(1), mul(Ast::Num(2), Ast::Num(3))), Ast::Num(4))));
+
+        let m = Memo::new();
+        let b = b"1*2*3";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Data(new(END_OF_INPUT, b""), mul(mul(Ast::Num(1), Ast::Num(2)), Ast::Num(3))));
+
+        let m = Memo::new();
+        let b = b"1;";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Data(new(END_OF_INPUT, b";"), Ast::Num(1)));
+
+        let m = Memo::new();
+        let b = b"1-;";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Data(new(END_OF_INPUT, b"-;"), Ast::Num(1)));
+    }
+
+    #[test]
+    fn left_recursion_error() {
+        let m = Memo::new();
+        let b = b";";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Error(b"", Err::Token));
+
+        // Only the left-recursive alternative
+        fn only<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, Ast, Err>) -> ::ParseResult<'a, u8, Ast, Err> {
+            left_rec(i, m, 0, |i| only(i, m).bind(|i, a| num(i).map(|b| sub(a, b))))
+        }
+
+        let m = Memo::new();
+        let b = b"123";
+
+        assert_eq!(only(new(END_OF_INPUT, b), &m).into_inner(), State::Error(&b[..], Err::Seed));
+    }
+
+    #[test]
+    fn left_recursion_incomplete() {
+        let m = Memo::new();
+        let b = b"1-2";
+
+        assert_eq!(expr(new(DEFAULT, b), &m).into_inner(), State::Incomplete(1));
+
+        let m = Memo::new();
+        let b = b"1-2";
+
+        assert_eq!(expr(new(END_OF_INPUT, b), &m).into_inner(), State::Data(new(END_OF_INPUT, b""), sub(Ast::Num(1), Ast::Num(2))));
+    }
+
+    #[test]
+    fn left_recursion_no_progress() {
+        let n = Cell::new(0);
+
+        // a = a | ''
+        fn empty<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, usize, Err>, n: &Cell<usize>) -> ::ParseResult<'a, u8, usize, Err> {
+            left_rec(i, m, 0, |i| {
+                n.set(n.get() + 1);
+
+                or(i, |i| empty(i, m, n).map(|c| c + 1), |i| i.ret(0))
+            })
+        }
+
+        let m = Memo::new();
+        let b = b"a";
+
+        assert_eq!(empty(new(END_OF_INPUT, b), &m, &n).into_inner(), State::Data(new(END_OF_INPUT, b"a"), 0));
+        assert_eq!(n.get(), 2);
+    }
+
+    #[test]
+    fn indirect_left_recursion() {
+        // a = b 'x' | 'y'
+        // b = a 'z'
+        fn a<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, Vec<u8>, Err>) -> ::ParseResult<'a, u8, Vec<u8>, Err> {
+            left_rec(i, m, 0, |i| or(i,
+                |i| b(i, m).bind(|i, mut v| token(i, b'x').map_err(|_| Err::Token).map(|c| { v.push(c); v })),
+                |i| token(i, b'y').map_err(|_| Err::Token).map(|c| vec![c])))
+        }
+
+        fn b<'a>(i: ::Input<'a, u8>, m: &Memo<'a, u8, Vec<u8>, Err>) -> ::ParseResult<'a, u8, Vec<u8>, Err> {
+            a(i, m).bind(|i, mut v| token(i, b'z').map_err(|_| Err::Token).map(|c| { v.push(c); v }))
+        }
+
+        let m = Memo::new();
+        let s = b"yzxzx;";
+
+        assert_eq!(a(new(END_OF_INPUT, s), &m).into_inner(), State::Data(new(END_OF_INPUT, b";"), b"yzxzx".to_vec()));
+    }
+}
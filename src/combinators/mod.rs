@@ -4,13 +4,24 @@
 mod macros;
 
 pub mod bounded;
+pub mod expr;
+pub mod memo;
+pub mod permutation;
+pub mod recursive;
 
+use std::error;
+use std::fmt;
 use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use conv::ValueFrom;
 
 use {ParseResult, Input};
 
+use parsers::Error;
 use primitives::State;
 use primitives::{IntoInner, InputBuffer, InputClone};
+use primitives::input::{self, END_OF_INPUT};
 
 /// Applies the parser ``p`` exactly ``num`` times collecting all items into `T: FromIterator`.
 ///
@@ -39,6 +50,73 @@ pub fn count<'a, I, T, E, F, U>(i: Input<'a, I>, num: usize, p: F) -> ParseResul
     bounded::many(i, num, p)
 }
 
+/// Error produced by ``count_array``, contains the index of the item which failed together with
+/// the error from the item parser.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CountError<E> {
+    /// Index of the item which failed to parse.
+    pub index: usize,
+    /// The error from the item parser.
+    pub error: E,
+}
+
+impl<E> fmt::Display for CountError<E>
+  where E: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "item {}: {}", self.index, self.error)
+    }
+}
+
+impl<E> error::Error for CountError<E>
+  where E: error::Error {
+    fn description(&self) -> &str {
+        "failed to parse an item of a fixed count"
+    }
+}
+
+/// Applies the parser ``p`` exactly ``N`` times, collecting all items into an array.
+///
+/// Unlike ``count`` this does not allocate, the items are stored directly in the array. Errors
+/// from ``p`` are wrapped in a ``CountError`` containing the index of the failing item.
+///
+#[cfg_attr(feature = "verbose_error", doc = "
+```
+ use chomp::{Error, ParseError, parse_only, token};
+ use chomp::ascii::decimal;
+ use chomp::combinators::{CountError, count_array};
+
+ let octet = |i| decimal(i).bind(|i, n| token(i, b'.').map(|_| n));
+
+ let r: Result<[u8; 3], _> = parse_only(|i| count_array(i, &octet), b\"10.0.1.\");
+ assert_eq!(r, Ok([10, 0, 1]));
+
+ let r: Result<[u8; 3], _> = parse_only(|i| count_array(i, &octet), b\"10.0;1.\");
+ assert_eq!(r, Err(ParseError::Error(b\";1.\", CountError { index: 1, error: Error::Expected(b'.') })));
+```
+")]
+#[inline]
+pub fn count_array<'a, I, T, E, F, const N: usize>(i: Input<'a, I>, mut p: F) -> ParseResult<'a, I, [T; N], CountError<E>>
+  where I: Copy,
+        T: 'a,
+        E: 'a,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    let mut items: [Option<T>; N] = [(); N].map(|_| None);
+    let mut buf                   = i.clone();
+
+    for (index, item) in items.iter_mut().enumerate() {
+        match p(buf.clone()).into_inner() {
+            State::Data(b, t)     => {
+                buf   = b;
+                *item = Some(t);
+            },
+            State::Error(b, e)    => return i.replace(b).err(CountError { index, error: e }),
+            State::Incomplete(n)  => return i.incomplete(n),
+        }
+    }
+
+    buf.ret(items.map(|t| t.expect("count_array: all items are parsed")))
+}
+
 /// Tries the parser ``f``, on success it yields the parsed value, on failure ``default`` will be
 /// yielded instead.
 ///
@@ -105,6 +183,137 @@ pub fn or<'a, I, T, E, F, G>(i: Input<'a, I>, f: F, g: G) -> ParseResult<'a, I,
     }
 }
 
+/// Applies the parser ``p`` and fails if the predicate ``f`` does not accept the produced value.
+///
+/// The error is reported at the position where ``p`` started, an ``Error::Unexpected`` converted
+/// into the error type of ``p``.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// use chomp::{Input, U8Result, parse_only, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::verify;
+///
+/// fn port(i: Input<u8>) -> U8Result<u32> {
+///     parse!{i;
+///                    token(b':');
+///         let port = verify(decimal, |p: &u32| *p < 65536);
+///
+///         ret port
+///     }
+/// }
+///
+/// assert_eq!(parse_only(port, b":8080"), Ok(8080));
+/// assert!(parse_only(port, b":65536").is_err());
+/// # }
+/// ```
+#[inline]
+pub fn verify<'a, I, T, E, P, F>(i: Input<'a, I>, p: P, f: F) -> ParseResult<'a, I, T, E>
+  where T: 'a,
+        E: 'a + From<Error<I>>,
+        P: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E>,
+        F: FnOnce(&T) -> bool {
+    filter(i, p, |t| if f(&t) { Some(t) } else { None })
+}
+
+/// Applies the parser ``p`` and then the function ``f`` to the produced value, failing if ``f``
+/// returns ``None``.
+///
+/// This is a ``verify`` which also transforms the value. The error is reported at the position
+/// where ``p`` started, an ``Error::Unexpected`` converted into the error type of ``p``.
+///
+/// ```
+/// use chomp::{parse_only, any};
+/// use chomp::combinators::filter;
+///
+/// let hex = |i| filter(i, any, |c| (c as char).to_digit(16));
+///
+/// assert_eq!(parse_only(&hex, b"b"), Ok(11));
+/// assert!(parse_only(&hex, b"g").is_err());
+/// ```
+#[inline]
+pub fn filter<'a, I, T, U, E, P, F>(i: Input<'a, I>, p: P, f: F) -> ParseResult<'a, I, U, E>
+  where T: 'a,
+        U: 'a,
+        E: 'a + From<Error<I>>,
+        P: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E>,
+        F: FnOnce(T) -> Option<U> {
+    match p(i.clone()).into_inner() {
+        State::Data(b, t)    => match f(t) {
+            Some(u) => b.ret(u),
+            None    => i.err(From::from(Error::new())),
+        },
+        State::Error(b, e)   => i.replace(b).err(e),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
+/// Applies the parser ``p`` and then the fallible function ``f`` to the produced value.
+///
+/// An ``Err`` from ``f`` is reported at the position where ``p`` started. Like ``bind``, errors
+/// from ``p`` are converted into the error type of ``f`` using the ``From`` trait.
+///
+/// ```
+/// use std::str;
+///
+/// use chomp::{ParseError, parse_only, take_while1};
+/// use chomp::combinators::map_res;
+///
+/// let p = |i| map_res(i, |i| take_while1(i, |c| c != b' ').map_err(|_| "no word"),
+///                     |w| str::from_utf8(w).map_err(|_| "not utf-8"));
+///
+/// assert_eq!(parse_only(&p, b"abc def"), Ok("abc"));
+/// assert_eq!(parse_only(&p, b"\xff def"), Err(ParseError::Error(b"\xff def", "not utf-8")));
+/// ```
+#[inline]
+pub fn map_res<'a, I, T, U, E, V, P, F>(i: Input<'a, I>, p: P, f: F) -> ParseResult<'a, I, U, V>
+  where T: 'a,
+        U: 'a,
+        E: 'a,
+        V: 'a + From<E>,
+        P: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E>,
+        F: FnOnce(T) -> Result<U, V> {
+    match p(i.clone()).into_inner() {
+        State::Data(b, t)    => match f(t) {
+            Ok(u)  => b.ret(u),
+            Err(e) => i.err(e),
+        },
+        State::Error(b, e)   => i.replace(b).err(From::from(e)),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
+/// Applies the parser ``p``, if it fails ``f`` is applied to the original input together with
+/// the error.
+///
+/// Unlike ``or`` the error of ``p`` is available for recovery or to produce a different error.
+/// Incomplete state is propagated.
+///
+/// ```
+/// use chomp::{ParseError, parse_only, token};
+///
+/// use chomp::combinators::or_else;
+///
+/// let p = |i| or_else(i, |i| token(i, b'a'), |i, _| i.err("expected 'a'"));
+///
+/// assert_eq!(parse_only(&p, b"abc"), Ok(b'a'));
+/// assert_eq!(parse_only(&p, b"bc"), Err(ParseError::Error(b"bc", "expected 'a'")));
+/// ```
+#[inline]
+pub fn or_else<'a, I, T, E, V, P, F>(i: Input<'a, I>, p: P, f: F) -> ParseResult<'a, I, T, V>
+  where T: 'a,
+        E: 'a,
+        V: 'a,
+        P: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E>,
+        F: FnOnce(Input<'a, I>, E) -> ParseResult<'a, I, T, V> {
+    match p(i.clone()).into_inner() {
+        State::Data(b, t)    => b.ret(t),
+        State::Error(_, e)   => f(i, e),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
 /// Parses many instances of ``f`` until it does no longer match, collecting all matches into the
 /// type `T: FromIterator`.
 ///
@@ -179,7 +388,7 @@ pub fn many1<'a, I, T, E, F, U>(i: Input<'a, I>, f: F) -> ParseResult<'a, I, T,
 /// assert_eq!(r, Ok(vec![91, 03, 20]));
 /// ```
 #[inline]
-pub fn sep_by<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, mut p: R, mut sep: F) -> ParseResult<'a, I, T, E>
+pub fn sep_by<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, p: R, sep: F) -> ParseResult<'a, I, T, E>
   where I: Copy,
         U: 'a,
         V: 'a,
@@ -188,18 +397,7 @@ pub fn sep_by<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, mut p: R, mut sep: F)
         E: From<N>,
         R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
         F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
-    // If we have parsed at least one item
-    let mut item = false;
-    // Add sep in front of p if we have read at least one item
-    let parser   = |i| (if item {
-            sep(i).map(|_| ())
-        } else {
-            i.ret(())
-        })
-        .then(&mut p)
-        .inspect(|_| item = true);
-
-    bounded::many(i, .., parser)
+    bounded::sep_by(i, .., p, sep)
 }
 
 
@@ -220,7 +418,7 @@ pub fn sep_by<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, mut p: R, mut sep: F)
 /// assert_eq!(r, Ok(vec![91, 03, 20]));
 /// ```
 #[inline]
-pub fn sep_by1<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, mut p: R, mut sep: F) -> ParseResult<'a, I, T, E>
+pub fn sep_by1<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, p: R, sep: F) -> ParseResult<'a, I, T, E>
   where I: Copy,
         U: 'a,
         V: 'a,
@@ -229,18 +427,7 @@ pub fn sep_by1<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, mut p: R, mut sep: F
         E: From<N>,
         R: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
         F: FnMut(Input<'a, I>) -> ParseResult<'a, I, V, N> {
-    // If we have parsed at least one item
-    let mut item = false;
-    // Add sep in front of p if we have read at least one item
-    let parser   = |i| (if item {
-            sep(i).map(|_| ())
-        } else {
-            i.ret(())
-        })
-        .then(&mut p)
-        .inspect(|_| item = true);
-
-    bounded::many(i, 1.., parser)
+    bounded::sep_by(i, 1.., p, sep)
 }
 
 /// Applies the parser `R` multiple times until the parser `F` succeeds and returns a
@@ -269,6 +456,59 @@ pub fn many_till<'a, I, T, E, R, F, U, N, V>(i: Input<'a, I>, p: R, end: F) -> P
     bounded::many_till(i, .., p, end)
 }
 
+/// Parses many instances of ``p`` until it does no longer match, folding all matches into an
+/// accumulator starting at ``init`` using ``f``.
+///
+/// Note: If the last parser succeeds on the last input item then this parser is still considered
+/// incomplete as there might be more data to fill.
+///
+/// Note: Does not allocate, unlike ``many``.
+///
+/// ```
+/// use chomp::{parse_only, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::fold_many;
+///
+/// let r = parse_only(|i| fold_many(i, 0, |i| decimal(i).bind(|i, n| token(i, b',').map(|_| n)),
+///                                  |acc, n: u32| acc + n),
+///                    b"1,20,300,;");
+///
+/// assert_eq!(r, Ok(321));
+/// ```
+#[inline]
+pub fn fold_many<'a, I, T, E, P, F, U>(i: Input<'a, I>, init: T, p: P, f: F) -> ParseResult<'a, I, T, E>
+  where I: Copy,
+        U: 'a,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(T, U) -> T {
+    bounded::fold_many(i, .., init, p, f)
+}
+
+/// Parses many instances of ``p`` until it does no longer match, calling ``f`` with each match.
+///
+/// Note: If the last parser succeeds on the last input item then this parser is still considered
+/// incomplete as there might be more data to fill.
+///
+/// Note: Does not allocate, unlike ``many``.
+///
+/// ```
+/// use chomp::{parse_only, any};
+/// use chomp::combinators::for_each_many;
+///
+/// let mut buf = vec![b'x'];
+///
+/// assert_eq!(parse_only(|i| for_each_many(i, any, |c| buf.push(c)), b"abc"), Ok(()));
+/// assert_eq!(buf, b"xabc");
+/// ```
+#[inline]
+pub fn for_each_many<'a, I, E, P, F, U>(i: Input<'a, I>, p: P, f: F) -> ParseResult<'a, I, (), E>
+  where I: Copy,
+        U: 'a,
+        P: FnMut(Input<'a, I>) -> ParseResult<'a, I, U, E>,
+        F: FnMut(U) {
+    bounded::for_each_many(i, .., p, f)
+}
+
 /// Runs the given parser until it fails, discarding matched input.
 ///
 /// Incomplete state will be propagated.
@@ -343,6 +583,150 @@ pub fn matched_by<'a, I, T, E, F>(i: Input<'a, I>, f: F) -> ParseResult<'a, I, (
     }
 }
 
+/// Parses a length using ``l`` and then returns a slice of exactly that many items.
+///
+/// If fewer items than the length are available ``Incomplete`` is reported with the number of
+/// items still missing. A failing ``l`` fails with ``LengthError::Length`` and a length which
+/// cannot be represented as a ``usize`` fails with ``LengthError::Overflow`` at the start of the
+/// length.
+///
+/// ```
+/// use chomp::{parse_only, any};
+/// use chomp::combinators::length_data;
+///
+/// assert_eq!(parse_only(|i| length_data(i, any), b"\x03abcd"), Ok(&b"abc"[..]));
+/// ```
+#[inline]
+pub fn length_data<'a, I, N, E, L>(i: Input<'a, I>, l: L) -> ParseResult<'a, I, &'a [I], LengthError<E>>
+  where I: Copy,
+        N: 'a,
+        E: 'a,
+        usize: ValueFrom<N>,
+        L: FnOnce(Input<'a, I>) -> ParseResult<'a, I, N, E> {
+    length_slice(i, l)
+}
+
+/// Implementation of ``length_data`` for any value error type, shared with ``length_value``.
+#[inline]
+fn length_slice<'a, I, N, E, F, L>(i: Input<'a, I>, l: L) -> ParseResult<'a, I, &'a [I], LengthError<E, F>>
+  where I: Copy,
+        N: 'a,
+        E: 'a,
+        F: 'a,
+        usize: ValueFrom<N>,
+        L: FnOnce(Input<'a, I>) -> ParseResult<'a, I, N, E> {
+    match l(i.clone()).into_inner() {
+        State::Data(b, n)    => {
+            let buf = b.buffer();
+
+            match usize::value_from(n) {
+                Ok(n) if n <= buf.len() => b.replace(&buf[n..]).ret(&buf[..n]),
+                Ok(n)                   => i.incomplete(n - buf.len()),
+                Err(_)                  => i.err(LengthError::Overflow),
+            }
+        },
+        State::Error(b, e)   => i.replace(b).err(LengthError::Length(e)),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
+/// Error produced by ``length_data`` and ``length_value``.
+///
+/// ``length_data`` does not parse a value, its errors use the default ``()`` for ``F``.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum LengthError<E, F = ()> {
+    /// The length parser failed.
+    Length(E),
+    /// The length does not fit in a ``usize``.
+    Overflow,
+    /// The value parser failed.
+    Value(F),
+    /// The value parser required more items than the length allowed.
+    Truncated,
+    /// The value parser did not consume all the items of the length.
+    Trailing,
+}
+
+impl<E, F> fmt::Display for LengthError<E, F>
+  where E: fmt::Display,
+        F: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LengthError::Length(ref e) => write!(f, "length: {}", e),
+            LengthError::Overflow      => write!(f, "length does not fit in usize"),
+            LengthError::Value(ref e)  => write!(f, "value: {}", e),
+            LengthError::Truncated     => write!(f, "value exceeds its length"),
+            LengthError::Trailing      => write!(f, "value does not fill its length"),
+        }
+    }
+}
+
+impl<E, F> error::Error for LengthError<E, F>
+  where E: error::Error,
+        F: error::Error {
+    fn description(&self) -> &str {
+        match *self {
+            LengthError::Length(_) => "failed to parse length",
+            LengthError::Overflow  => "length does not fit in usize",
+            LengthError::Value(_)  => "failed to parse value",
+            LengthError::Truncated => "value exceeds its length",
+            LengthError::Trailing  => "value does not fill its length",
+        }
+    }
+}
+
+/// Parses a length using ``l`` and then applies ``p`` to a slice of exactly that many items.
+///
+/// ``p`` is run on the slice as a finite input (``END_OF_INPUT`` is set) and has to consume all
+/// of it, leftover items fail with ``LengthError::Trailing`` and a ``p`` needing more items fails
+/// with ``LengthError::Truncated``. Error positions refer to the original input. Errors of the
+/// length and ``Incomplete`` are reported in the same way as in ``length_data``.
+///
+/// ```
+/// use chomp::{ParseError, parse_only, any, take_while};
+/// use chomp::combinators::{LengthError, length_value};
+///
+/// let p = |i| length_value(i, any, |i| take_while(i, |c| c != b' '));
+///
+/// assert_eq!(parse_only(&p, b"\x03abc def"), Ok(&b"abc"[..]));
+/// assert_eq!(parse_only(&p, b"\x05abc def"), Err(ParseError::Error(&b" def"[..], LengthError::Trailing)));
+/// ```
+#[inline]
+pub fn length_value<'a, I, N, T, E, F, L, P>(i: Input<'a, I>, l: L, p: P) -> ParseResult<'a, I, T, LengthError<E, F>>
+  where I: Copy,
+        N: 'a,
+        T: 'a,
+        E: 'a,
+        F: 'a,
+        usize: ValueFrom<N>,
+        L: FnOnce(Input<'a, I>) -> ParseResult<'a, I, N, E>,
+        P: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, F> {
+    let buf = i.buffer();
+
+    match length_slice(i.clone(), l).into_inner() {
+        State::Data(b, d)    => {
+            // Remainders of p end with d, extend them to the end of the original input
+            let outer = |rest: &[I]| &buf[buf.len() - b.buffer().len() - rest.len()..];
+
+            match p(input::new(END_OF_INPUT, d)).into_inner() {
+                State::Data(r, t)    => {
+                    let rest = r.buffer();
+
+                    if rest.is_empty() {
+                        b.ret(t)
+                    } else {
+                        i.replace(outer(rest)).err(LengthError::Trailing)
+                    }
+                },
+                State::Error(r, e)   => i.replace(outer(r)).err(LengthError::Value(e)),
+                State::Incomplete(_) => i.replace(outer(&[])).err(LengthError::Truncated),
+            }
+        },
+        State::Error(b, e)   => i.replace(b).err(e),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
 /// Applies the parser `F` without consuming any input.
 ///
 /// ```
@@ -363,6 +747,105 @@ pub fn look_ahead<'a, I, T, E, F>(i: Input<'a, I>, f: F) -> ParseResult<'a, I, T
     }
 }
 
+/// The state of an `Iter` once iteration has stopped, obtained through `Iter::end_state`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EndState<'a, I: 'a, E> {
+    /// Iteration was stopped by the caller, eg. through `take`, before the parser failed.
+    Stopped,
+    /// The parser failed with the error `E` at the given position.
+    Error(&'a [I], E),
+    /// The parser requested more input.
+    Incomplete(usize),
+}
+
+/// Iterator applying a parser repeatedly, created by `iter`.
+///
+/// Yields the values of the parser until it fails, after which the iterator will only yield
+/// `None`.
+#[derive(Debug)]
+pub struct Iter<'a, I: 'a, T, E, F> {
+    /// Last state of the parser
+    state:  EndState<'a, I, E>,
+    /// Parser to execute once for each iteration
+    parser: F,
+    /// Remaining buffer, positioned after the last successful parse
+    buf:    Input<'a, I>,
+    _t:     PhantomData<T>,
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a, F> Iter<'a, I, T, E, F>
+  where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    /// Destructures the iterator returning the position just after the last successful parse as
+    /// well as the state of the last attempt to parse data.
+    #[inline]
+    pub fn end_state(self) -> (Input<'a, I>, EndState<'a, I, E>) {
+        (self.buf, self.state)
+    }
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a, F> Iterator for Iter<'a, I, T, E, F>
+  where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if let EndState::Stopped = self.state {
+            match (self.parser)(self.buf.clone()).into_inner() {
+                State::Data(b, v)    => {
+                    self.buf = b;
+
+                    return Some(v);
+                },
+                State::Error(b, e)   => self.state = EndState::Error(b, e),
+                State::Incomplete(n) => self.state = EndState::Incomplete(n),
+            }
+        }
+
+        None
+    }
+}
+
+/// Creates an iterator which lazily applies the parser ``f`` until it fails, yielding the parsed
+/// values.
+///
+/// Use `Iter::end_state` to obtain the remaining input and the reason iteration stopped once
+/// done, it is up to the caller to decide how to treat the final state. To mirror ``many``,
+/// errors and incomplete on the last slice should be treated as the end of the matches.
+///
+/// ```
+/// use chomp::{Input, U8Result, parse_only, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::{EndState, iter};
+/// use chomp::primitives::InputBuffer;
+///
+/// // Sums at most 3 numbers, each followed by a comma
+/// fn sum(i: Input<u8>) -> U8Result<u32> {
+///     let mut it = iter(i, |i| decimal::<u32>(i).bind(|i, n| token(i, b',').map(|_| n)));
+///
+///     let n: u32 = it.by_ref().take(3).sum();
+///
+///     match it.end_state() {
+///         (i, EndState::Incomplete(n)) if !i.is_last_slice() => i.incomplete(n),
+///         (i, _)                                             => i.ret(n),
+///     }
+/// }
+///
+/// assert_eq!(parse_only(sum, b"1,2,"), Ok(3));
+/// assert_eq!(parse_only(sum, b"1,2,3,4,"), Ok(6));
+/// ```
+#[inline]
+pub fn iter<'a, I, T, E, F>(i: Input<'a, I>, f: F) -> Iter<'a, I, T, E, F>
+  where T: 'a,
+        E: 'a,
+        F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    Iter {
+        state:  EndState::Stopped,
+        parser: f,
+        buf:    i,
+        _t:     PhantomData,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ParseResult;
@@ -371,7 +854,7 @@ mod test {
     use primitives::IntoInner;
     use super::*;
 
-    use parsers::{any, take, token, string};
+    use parsers::{any, satisfy, take, take_while, token, string};
 
     #[test]
     fn option_test() {
@@ -517,6 +1000,71 @@ mod test {
         assert_eq!(r.into_inner(), State::Error(b"bcd", "the error"));
     }
 
+    #[test]
+    fn fold_many_test() {
+        assert_eq!(fold_many(new(DEFAULT, b"abc;"), 0, |i| satisfy(i, |c| c != b';'), |n, _| n + 1).into_inner(), State::Data(new(DEFAULT, b";"), 3));
+        assert_eq!(fold_many(new(DEFAULT, b"abc"), 0, any, |n, _| n + 1).into_inner(), State::Incomplete(1));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"abc"), 0, any, |n, _| n + 1).into_inner(), State::Data(new(END_OF_INPUT, b""), 3));
+        assert_eq!(fold_many(new(END_OF_INPUT, b""), 7, any, |n, _| n + 1).into_inner(), State::Data(new(END_OF_INPUT, b""), 7));
+        assert_eq!(fold_many(new(END_OF_INPUT, b"ab"), Vec::new(), any, |mut v, c| { v.push(c); v }).into_inner(), State::Data(new(END_OF_INPUT, b""), vec![b'a', b'b']));
+    }
+
+    #[test]
+    fn for_each_many_test() {
+        let mut v = Vec::new();
+        assert_eq!(for_each_many(new(DEFAULT, b"abc;"), |i| satisfy(i, |c| c != b';'), |c| v.push(c)).into_inner(), State::Data(new(DEFAULT, b";"), ()));
+        assert_eq!(v, b"abc");
+
+        let mut v = Vec::new();
+        assert_eq!(for_each_many(new(DEFAULT, b"abc"), any, |c| v.push(c)).into_inner(), State::Incomplete(1));
+        assert_eq!(v, b"abc");
+
+        let mut v = Vec::new();
+        assert_eq!(for_each_many(new(END_OF_INPUT, b"ab"), any, |c| v.push(c)).into_inner(), State::Data(new(END_OF_INPUT, b""), ()));
+        assert_eq!(v, b"ab");
+    }
+
+    #[test]
+    fn count_array_test() {
+        let r: ParseResult<_, [u8; 0], _> = count_array(new(DEFAULT, b"abc"), any);
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"abc"), []));
+        let r: ParseResult<_, [u8; 2], _> = count_array(new(DEFAULT, b"abc"), any);
+        assert_eq!(r.into_inner(), State::Data(new(DEFAULT, b"c"), [b'a', b'b']));
+        let r: ParseResult<_, [u8; 3], _> = count_array(new(DEFAULT, b"ab"), any);
+        assert_eq!(r.into_inner(), State::Incomplete(1));
+        let r: ParseResult<_, [&[u8]; 2], _> = count_array(new(END_OF_INPUT, b"aabbc"), |i| take(i, 2));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b"c"), [&b"aa"[..], &b"bb"[..]]));
+
+        let r: ParseResult<_, [u8; 3], _> = count_array(new(END_OF_INPUT, b"aaba"), |i| token(i, b'a').map_err(|_| "token err"));
+        assert_eq!(r.into_inner(), State::Error(b"ba", CountError { index: 2, error: "token err" }));
+        let r: ParseResult<_, [String; 3], _> = count_array(new(END_OF_INPUT, b"abc"), |i| any(i).map(|c| (c as char).to_string()));
+        assert_eq!(r.into_inner(), State::Data(new(END_OF_INPUT, b""), ["a".to_owned(), "b".to_owned(), "c".to_owned()]));
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut it = iter(new(DEFAULT, b"aab"), |i| token(i, b'a').map_err(|_| "token err"));
+        assert_eq!(it.next(), Some(b'a'));
+        assert_eq!(it.next(), Some(b'a'));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+        assert_eq!(it.end_state(), (new(DEFAULT, b"b"), EndState::Error(&b"b"[..], "token err")));
+
+        let mut it = iter(new(DEFAULT, b"aa"), |i| token(i, b'a'));
+        assert_eq!(it.by_ref().count(), 2);
+        assert_eq!(it.end_state(), (new(DEFAULT, b""), EndState::Incomplete(1)));
+
+        let mut it = iter(new(END_OF_INPUT, b"abc"), any);
+        assert_eq!(it.by_ref().take(2).collect::<Vec<_>>(), vec![b'a', b'b']);
+        assert_eq!(it.end_state(), (new(END_OF_INPUT, b"c"), EndState::Stopped));
+
+        let mut n = 0;
+        let mut it = iter(new(END_OF_INPUT, b"abc"), |i| if n == 0 { n += 1; any(i).map_err(|_| "any err") } else { take(i, 1).map_err(|_| "take err").then(|i| i.err("the error")) });
+        assert_eq!(it.next(), Some(b'a'));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.end_state(), (new(END_OF_INPUT, b"bc"), EndState::Error(&b"c"[..], "the error")));
+    }
+
     #[test]
     fn matched_by_test() {
         assert_eq!(matched_by(new(DEFAULT, b"abc"), any).into_inner(), State::Data(new(DEFAULT, b"bc"), (&b"a"[..], b'a')));
@@ -525,6 +1073,67 @@ mod test {
         assert_eq!(matched_by(new(DEFAULT, b""), any).into_inner(), State::Incomplete(1));
     }
 
+    #[test]
+    fn verify_test() {
+        assert_eq!(verify(new(DEFAULT, b"abc"), any, |c| *c == b'a').into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!(verify(new(DEFAULT, b"abc"), any, |c| *c == b'b').into_inner(), State::Error(&b"abc"[..], Error::new()));
+        assert_eq!(verify(new(DEFAULT, b"abc"), |i| token(i, b'b'), |_| true).into_inner(), token(new(DEFAULT, b"abc"), b'b').into_inner());
+        assert_eq!(verify(new(DEFAULT, b""), any, |_| true).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn filter_test() {
+        assert_eq!(filter(new(DEFAULT, b"abc"), any, |c| if c == b'a' { Some(1) } else { None }).into_inner(), State::Data(new(DEFAULT, b"bc"), 1));
+        assert_eq!(filter(new(DEFAULT, b"abc"), |i| take(i, 2), |_| None::<()>).into_inner(), State::Error(&b"abc"[..], Error::new()));
+        assert_eq!(filter(new(DEFAULT, b"a"), |i| take(i, 2), Some).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn map_res_test() {
+        let p = |i| any(i).map_err(|_| "any error");
+
+        assert_eq!(map_res(new(DEFAULT, b"abc"), p, |c| Ok::<_, &str>(c + 1)).into_inner(), State::Data(new(DEFAULT, b"bc"), b'b'));
+        assert_eq!(map_res(new(DEFAULT, b"abc"), p, |_| Err::<(), _>("my error")).into_inner(), State::Error(&b"abc"[..], "my error"));
+        assert_eq!(map_res(new(DEFAULT, b"abc"), |i| any(i).map_err(|_| "any error").then(|i| i.err::<(), _>("inner error")), |_| Ok::<(), &str>(())).into_inner(), State::Error(&b"bc"[..], "inner error"));
+        assert_eq!(map_res(new(DEFAULT, b""), p, |_| Ok::<(), &str>(())).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn or_else_test() {
+        assert_eq!(or_else(new(DEFAULT, b"abc"), any, |i, _| i.err::<_, ()>(())).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!(or_else(new(DEFAULT, b"abc"), |i| any(i).map_err(|_| "any error").then(|i| i.err::<u8, _>("err")), |i, e| i.ret::<_, ()>(e.len() as u8)).into_inner(), State::Data(new(DEFAULT, b"abc"), 3));
+        assert_eq!(or_else(new(DEFAULT, b"abc"), |i| i.err::<u8, _>("err"), |i, e| i.replace(&b"c"[..]).err(e)).into_inner(), State::Error(&b"c"[..], "err"));
+        assert_eq!(or_else(new(END_OF_INPUT, b"a"), |i| take(i, 2), |i, _| i.ret::<_, ()>(&b""[..])).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn length_data_test() {
+        assert_eq!(length_data(new(DEFAULT, b"\x02abc"), any).into_inner(), State::Data(new(DEFAULT, b"c"), &b"ab"[..]));
+        assert_eq!(length_data(new(DEFAULT, b"\x00abc"), any).into_inner(), State::Data(new(DEFAULT, b"abc"), &b""[..]));
+        assert_eq!(length_data(new(END_OF_INPUT, b"\x03abc"), any).into_inner(), State::Data(new(END_OF_INPUT, b""), &b"abc"[..]));
+        assert_eq!(length_data(new(DEFAULT, b"\x05ab"), any).into_inner(), State::Incomplete(3));
+        assert_eq!(length_data(new(END_OF_INPUT, b"\x05ab"), any).into_inner(), State::Incomplete(3));
+        assert_eq!(length_data(new(DEFAULT, b""), any).into_inner(), State::Incomplete(1));
+        assert_eq!(length_data(new(DEFAULT, b"abc"), |i| i.err::<u8, _>("my error")).into_inner(), State::Error(&b"abc"[..], LengthError::Length("my error")));
+        assert_eq!(length_data(new(DEFAULT, b"abc"), |i| i.ret::<_, ()>(1000u32)).into_inner(), State::Incomplete(997));
+        assert_eq!(length_data(new(DEFAULT, b"\xffabc"), |i| any(i).map(|n| n as i8)).into_inner(), State::Error(&b"\xffabc"[..], LengthError::Overflow));
+    }
+
+    #[test]
+    fn length_value_test() {
+        let inner = |i| take_while(i, |c| c != b' ').map_err(|_| "value error");
+        let len   = |i| any(i).map_err(|_| "length error");
+
+        assert_eq!(length_value(new(DEFAULT, b"\x03abc def"), len, inner).into_inner(), State::Data(new(DEFAULT, b" def"), &b"abc"[..]));
+        assert_eq!(length_value(new(DEFAULT, b"\x03abcdef"), len, inner).into_inner(), State::Data(new(DEFAULT, b"def"), &b"abc"[..]));
+        assert_eq!(length_value(new(DEFAULT, b"\x05abc def"), len, inner).into_inner(), State::Error(&b" def"[..], LengthError::Trailing));
+        assert_eq!(length_value(new(DEFAULT, b"\x05abc"), len, inner).into_inner(), State::Incomplete(2));
+        assert_eq!(length_value(new(DEFAULT, b""), len, inner).into_inner(), State::Incomplete(1));
+        assert_eq!(length_value(new(DEFAULT, b"abc"), |i| i.err::<u8, _>("my error"), inner).into_inner(), State::Error(&b"abc"[..], LengthError::Length("my error")));
+        assert_eq!(length_value(new(DEFAULT, b"\x02abc"), len, |i| token(i, b'a').then(|i| token(i, b'c')).map_err(|_| "value error")).into_inner(), State::Error(&b"bc"[..], LengthError::Value("value error")));
+        assert_eq!(length_value(new(DEFAULT, b"\x02abc"), len, |i| string(i, b"abc").map_err(|_| "value error")).into_inner(), State::Error(&b"c"[..], LengthError::Truncated));
+    }
+
     #[test]
     fn sep_by_test() {
         assert_eq!(sep_by(new(END_OF_INPUT, b""), any, |i| token(i, b';')).into_inner(), State::Data(new(END_OF_INPUT, b""), vec![]));
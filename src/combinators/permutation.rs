@@ -0,0 +1,349 @@
+//! Permutation parsers, matching a set of parsers in any order.
+//!
+//! A permutation is a tuple of members, where each member is either `required` or `optional`.
+//! The `permutation` combinator will attempt the members which have not yet matched in the order
+//! they appear in the tuple until none of them match, the result is a tuple of the member values
+//! in the order of the members, `Option<T>` for optional members.
+//!
+//! ```
+//! # #[macro_use] extern crate chomp;
+//! # fn main() {
+//! use chomp::{Input, parse_only, string, token};
+//! use chomp::ascii::decimal;
+//! use chomp::combinators::permutation::{permutation, required, optional};
+//!
+//! fn field<'a>(i: Input<'a, u8>, name: &'static [u8]) -> chomp::U8Result<'a, u32> {
+//!     parse!{i;
+//!         string(name);
+//!         token(b'=');
+//!         let v = decimal();
+//!         token(b';');
+//!
+//!         ret v
+//!     }
+//! }
+//!
+//! let p = |i| permutation(i, (
+//!     required(|i| field(i, b"width")),
+//!     required(|i| field(i, b"height")),
+//!     optional(|i| field(i, b"depth")),
+//! ));
+//!
+//! assert_eq!(parse_only(&p, b"height=2;width=3;"), Ok((3, 2, None)));
+//! assert_eq!(parse_only(&p, b"depth=1;width=3;height=2;"), Ok((3, 2, Some(1))));
+//! # }
+//! ```
+
+use std::error;
+use std::fmt;
+
+use {Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// Error produced by `permutation`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Error {
+    /// No more members matched but the required members at the contained indices did not match.
+    ///
+    /// The error is positioned after the last matched member.
+    Missing(Vec<usize>),
+    /// The member at the contained index matched a second time, the error is positioned at the
+    /// start of the duplicate.
+    Duplicate(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Missing(ref m)  => write!(f, "missing required permutation members {:?}", m),
+            Error::Duplicate(n)    => write!(f, "duplicate permutation member {}", n),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Missing(_)   => "missing required permutation members",
+            Error::Duplicate(_) => "duplicate permutation member",
+        }
+    }
+}
+
+/// A member of a permutation.
+///
+/// Implemented by `Required` and `Optional`.
+pub trait Member<'a, I: 'a> {
+    /// Value produced by the member parser.
+    type Value: 'a;
+    /// Error produced by the member parser, only used to determine that the member did not match.
+    type Error: 'a;
+    /// Type of the member in the resulting tuple.
+    type Output;
+
+    /// Runs the member parser.
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, Self::Value, Self::Error>;
+
+    /// Converts the value of the member, if it matched, into the output. `None` if the member is
+    /// missing.
+    fn finish(v: Option<Self::Value>) -> Option<Self::Output>;
+}
+
+/// A permutation member which has to match exactly once, created by `required`.
+#[derive(Debug)]
+pub struct Required<F>(F);
+
+/// A permutation member which can match at most once, created by `optional`.
+#[derive(Debug)]
+pub struct Optional<F>(F);
+
+/// Creates a member which must match exactly once, the output is the value of the parser `p`.
+#[inline]
+pub fn required<F>(p: F) -> Required<F> {
+    Required(p)
+}
+
+/// Creates a member which can match at most once, the output is `Some` containing the value of
+/// the parser `p` if it matched, `None` otherwise.
+#[inline]
+pub fn optional<F>(p: F) -> Optional<F> {
+    Optional(p)
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a, F> Member<'a, I> for Required<F>
+  where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    type Value  = T;
+    type Error  = E;
+    type Output = T;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        (self.0)(i)
+    }
+
+    #[inline]
+    fn finish(v: Option<T>) -> Option<T> {
+        v
+    }
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a, F> Member<'a, I> for Optional<F>
+  where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    type Value  = T;
+    type Error  = E;
+    type Output = Option<T>;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        (self.0)(i)
+    }
+
+    #[inline]
+    fn finish(v: Option<T>) -> Option<Option<T>> {
+        Some(v)
+    }
+}
+
+/// A tuple of permutation members.
+///
+/// Implemented for tuples of up to 12 `Member`s.
+pub trait Members<'a, I: 'a> {
+    /// Tuple of the member outputs.
+    type Output;
+
+    /// Runs the permutation, see `permutation`.
+    fn run(self, i: Input<'a, I>) -> ParseResult<'a, I, Self::Output, Error>;
+}
+
+/// Result of attempting a member.
+enum Matched<'a, I: 'a, T> {
+    /// The member matched, contains the remainder and the value.
+    Yes(Input<'a, I>, T),
+    /// The member did not match.
+    No,
+    /// The member requested more input.
+    Incomplete(usize),
+}
+
+/// Attempts the member `m` on `i`, backtracking on failure. Incomplete is treated as not matching
+/// on the last slice.
+#[inline]
+fn attempt<'a, I, M>(i: &Input<'a, I>, m: &mut M) -> Matched<'a, I, M::Value>
+  where I: Copy,
+        M: Member<'a, I> {
+    match m.parse(i.clone()).into_inner() {
+        State::Data(b, v)    => Matched::Yes(b, v),
+        State::Error(_, _)   => Matched::No,
+        State::Incomplete(n) => if i.is_last_slice() {
+            Matched::No
+        } else {
+            Matched::Incomplete(n)
+        },
+    }
+}
+
+macro_rules! impl_members {
+    ( $($t:ident $p:ident $v:ident $n:tt),+ ) => {
+        impl<'a, I: 'a + Copy, $($t: Member<'a, I>),+> Members<'a, I> for ($($t,)+) {
+            type Output = ($($t::Output,)+);
+
+            #[inline]
+            fn run(self, i: Input<'a, I>) -> ParseResult<'a, I, Self::Output, Error> {
+                let ($(mut $p,)+) = self;
+                let mut i         = i;
+
+                $(let mut $v: Option<$t::Value> = None;)+
+
+                'members: loop {
+                    $(if $v.is_none() {
+                        match attempt(&i, &mut $p) {
+                            Matched::Yes(b, v)     => {
+                                i  = b;
+                                $v = Some(v);
+
+                                continue 'members;
+                            },
+                            Matched::No            => (),
+                            Matched::Incomplete(n) => return i.incomplete(n),
+                        }
+                    })+
+
+                    break;
+                }
+
+                // Members which already matched must not match again, members matching without
+                // consuming any input are not considered duplicates
+                $(if $v.is_some() {
+                    match attempt(&i, &mut $p) {
+                        Matched::Yes(b, _)     => if b.buffer().len() != i.buffer().len() {
+                            return i.err(Error::Duplicate($n));
+                        },
+                        Matched::No            => (),
+                        Matched::Incomplete(n) => return i.incomplete(n),
+                    }
+                })+
+
+                let mut missing = Vec::new();
+
+                $(let $v = $t::finish($v);
+
+                if $v.is_none() {
+                    missing.push($n);
+                })+
+
+                if let ($(Some($v),)+) = ($($v,)+) {
+                    i.ret(($($v,)+))
+                } else {
+                    i.err(Error::Missing(missing))
+                }
+            }
+        }
+    }
+}
+
+impl_members!(A pa va 0);
+impl_members!(A pa va 0, B pb vb 1);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6, H ph vh 7);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6, H ph vh 7, J pj vj 8);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6, H ph vh 7, J pj vj 8, K pk vk 9);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6, H ph vh 7, J pj vj 8, K pk vk 9, L pl vl 10);
+impl_members!(A pa va 0, B pb vb 1, C pc vc 2, D pd vd 3, E pe ve 4, F pf vf 5, G pg vg 6, H ph vh 7, J pj vj 8, K pk vk 9, L pl vl 10, M pm vm 11);
+
+/// Matches the members of the tuple `m` in any order, each at most once, yielding a tuple of
+/// their values in the order of the members.
+///
+/// Members which have not yet matched are attempted in order until none of them match. Fails with
+/// `Error::Duplicate` if a member which already matched would match again, and with
+/// `Error::Missing` listing all missing `required` members.
+///
+/// Incomplete state is propagated unless the input is the last slice, in which case the member is
+/// considered to not match.
+///
+/// ```
+/// use chomp::{ParseError, parse_only, token};
+/// use chomp::combinators::permutation::{Error, permutation, required, optional};
+///
+/// let p = |i| permutation(i, (required(|i| token(i, b'a')),
+///                             optional(|i| token(i, b'b')),
+///                             required(|i| token(i, b'c'))));
+///
+/// assert_eq!(parse_only(&p, b"cab"), Ok((b'a', Some(b'b'), b'c')));
+/// assert_eq!(parse_only(&p, b"ca"), Ok((b'a', None, b'c')));
+/// assert_eq!(parse_only(&p, b"b"), Err(ParseError::Error(b"", Error::Missing(vec![0, 2]))));
+/// assert_eq!(parse_only(&p, b"aca"), Err(ParseError::Error(b"a", Error::Duplicate(0))));
+/// ```
+#[inline]
+pub fn permutation<'a, I, M>(i: Input<'a, I>, m: M) -> ParseResult<'a, I, M::Output, Error>
+  where I: Copy,
+        M: Members<'a, I> {
+    m.run(i)
+}
+
+#[cfg(test)]
+mod test {
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::{IntoInner, State};
+    use parsers::{string, token};
+    use combinators::many;
+
+    use super::{Error, permutation, required, optional};
+
+    #[test]
+    fn single() {
+        assert_eq!(permutation(new(END_OF_INPUT, b"ab"), (required(|i| token(i, b'a')),)).into_inner(), State::Data(new(END_OF_INPUT, b"b"), (b'a',)));
+        assert_eq!(permutation(new(END_OF_INPUT, b"b"), (required(|i| token(i, b'a')),)).into_inner(), State::Error(b"b", Error::Missing(vec![0])));
+        assert_eq!(permutation(new(END_OF_INPUT, b"b"), (optional(|i| token(i, b'a')),)).into_inner(), State::Data(new(END_OF_INPUT, b"b"), (None,)));
+        assert_eq!(permutation(new(END_OF_INPUT, b"aa"), (optional(|i| token(i, b'a')),)).into_inner(), State::Error(b"a", Error::Duplicate(0)));
+    }
+
+    #[test]
+    fn any_order() {
+        let p = |i| permutation(i, (required(|i| string(i, b"foo")),
+                                    required(|i| string(i, b"bar")),
+                                    optional(|i| string(i, b"baz"))));
+
+        assert_eq!(p(new(END_OF_INPUT, b"foobarbaz;")).into_inner(), State::Data(new(END_OF_INPUT, b";"), (&b"foo"[..], &b"bar"[..], Some(&b"baz"[..]))));
+        assert_eq!(p(new(END_OF_INPUT, b"bazbarfoo;")).into_inner(), State::Data(new(END_OF_INPUT, b";"), (&b"foo"[..], &b"bar"[..], Some(&b"baz"[..]))));
+        assert_eq!(p(new(END_OF_INPUT, b"barfoo;")).into_inner(), State::Data(new(END_OF_INPUT, b";"), (&b"foo"[..], &b"bar"[..], None)));
+        assert_eq!(p(new(END_OF_INPUT, b"barfoo")).into_inner(), State::Data(new(END_OF_INPUT, b""), (&b"foo"[..], &b"bar"[..], None)));
+    }
+
+    #[test]
+    fn errors() {
+        let p = |i| permutation(i, (required(|i| string(i, b"foo")),
+                                    optional(|i| string(i, b"bar")),
+                                    required(|i| string(i, b"baz"))));
+
+        assert_eq!(p(new(END_OF_INPUT, b";")).into_inner(), State::Error(b";", Error::Missing(vec![0, 2])));
+        assert_eq!(p(new(END_OF_INPUT, b"bar;")).into_inner(), State::Error(b";", Error::Missing(vec![0, 2])));
+        assert_eq!(p(new(END_OF_INPUT, b"bazbar;")).into_inner(), State::Error(b";", Error::Missing(vec![0])));
+        assert_eq!(p(new(END_OF_INPUT, b"foobarbar;")).into_inner(), State::Error(b"bar;", Error::Duplicate(1)));
+        assert_eq!(p(new(END_OF_INPUT, b"foofoo")).into_inner(), State::Error(b"foo", Error::Duplicate(0)));
+    }
+
+    #[test]
+    fn incomplete() {
+        let p = |i| permutation(i, (required(|i| string(i, b"foo")),
+                                    optional(|i| string(i, b"bar"))));
+
+        assert_eq!(p(new(DEFAULT, b"")).into_inner(), State::Incomplete(3));
+        assert_eq!(p(new(DEFAULT, b"foob")).into_inner(), State::Incomplete(2));
+        assert_eq!(p(new(DEFAULT, b"foobar;;;")).into_inner(), State::Data(new(DEFAULT, b";;;"), (&b"foo"[..], Some(&b"bar"[..]))));
+        assert_eq!(p(new(END_OF_INPUT, b"foob")).into_inner(), State::Data(new(END_OF_INPUT, b"b"), (&b"foo"[..], None)));
+    }
+
+    #[test]
+    fn empty_match_is_not_duplicate() {
+        let p = |i| permutation(i, (required(|i| many(i, |i| token(i, b'a'))),
+                                    required(|i| token(i, b'b'))));
+
+        assert_eq!(p(new(END_OF_INPUT, b"b;")).into_inner(), State::Data(new(END_OF_INPUT, b";"), (vec![], b'b')));
+        assert_eq!(p(new(END_OF_INPUT, b"bab")).into_inner(), State::Error(b"ab", Error::Duplicate(0)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Recursive parsers defined using closures.
+//!
+//! Named `fn` items can refer to themselves and to each other, closures cannot. `fix` lets a
+//! closure refer to itself through its first parameter, while `Lazy` is a parser which can be
+//! referred to before it is defined, allowing mutually recursive grammars to be built at runtime.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use {Input, ParseResult};
+
+/// Applies the parser ``f``, which receives a reference to itself as the first parameter.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// use chomp::{parse_only, or, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::recursive::fix;
+///
+/// // Nested parenthesis around a number, with a configurable opening token:
+/// let open = b'[';
+/// let p    = |i| fix(i, |p, i| or(i,
+///     |i| parse!{i; token(open); let n = p(); token(b']'); ret n},
+///     decimal::<u32>));
+///
+/// assert_eq!(parse_only(&p, b"[[12]]"), Ok(12));
+/// # }
+/// ```
+#[inline]
+pub fn fix<'a, I, T, E, F>(i: Input<'a, I>, f: F) -> ParseResult<'a, I, T, E>
+  where F: Fn(&dyn Fn(Input<'a, I>) -> ParseResult<'a, I, T, E>, Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    fix_inner(&f, i)
+}
+
+fn fix_inner<'a, I, T, E, F>(f: &F, i: Input<'a, I>) -> ParseResult<'a, I, T, E>
+  where F: Fn(&dyn Fn(Input<'a, I>) -> ParseResult<'a, I, T, E>, Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    f(&|i| fix_inner(f, i), i)
+}
+
+/// The definition stored in a `Lazy`, accepting input of any lifetime.
+type Definition<'p, I, T, E> = Box<dyn for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, T, E> + 'p>;
+
+/// A parser which can be used before it has been defined.
+///
+/// Cloning a `Lazy` creates another handle to the same parser, which is how definitions refer to
+/// each other. The parser is then defined once using `Lazy::define` and applied using `lazy` or
+/// `Lazy::parse`.
+///
+/// The definition accepts input of any lifetime, so a grammar can be built once and reused on
+/// any number of buffers. This also means that the values and errors it produces cannot borrow
+/// from the input.
+///
+/// # Reference cycles
+///
+/// Definitions which refer to each other (or to themselves) form a reference cycle which is never
+/// freed on its own. Call `Lazy::clear` on one of the parsers in the cycle once the grammar is no
+/// longer needed.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// use chomp::{parse_only, token};
+/// use chomp::ascii::decimal;
+/// use chomp::combinators::recursive::{Lazy, lazy};
+///
+/// // sum  ::= atom ('+' sum)?
+/// // atom ::= number | '(' sum ')'
+/// let sum  = Lazy::new();
+/// let atom = Lazy::new();
+///
+/// let s = sum.clone();
+/// let a = atom.clone();
+///
+/// sum.define(move |i| parse!{i;
+///     let n = lazy(&a);
+///     let m = (token(b'+') >> lazy(&s)) <|> ret 0;
+///
+///     ret n + m
+/// });
+///
+/// let s = sum.clone();
+///
+/// atom.define(move |i| parse!{i;
+///     (token(b'(') >> lazy(&s) <* token(b')')) <|> decimal()
+/// });
+///
+/// assert_eq!(parse_only(|i| sum.parse(i), b"1+(2+3)+4;"), Ok(10u32));
+///
+/// sum.clear();
+/// # }
+/// ```
+pub struct Lazy<'p, I, T, E>(Rc<RefCell<Option<Definition<'p, I, T, E>>>>);
+
+impl<'p, I, T, E> Lazy<'p, I, T, E> {
+    /// Creates a new undefined parser.
+    #[inline]
+    pub fn new() -> Self {
+        Lazy(Rc::new(RefCell::new(None)))
+    }
+
+    /// Defines the parser, replacing any previous definition.
+    ///
+    /// # Panics
+    ///
+    /// If the parser is currently running.
+    #[inline]
+    pub fn define<F>(&self, f: F)
+      where F: for<'a> Fn(Input<'a, I>) -> ParseResult<'a, I, T, E> + 'p {
+        *self.0.borrow_mut() = Some(Box::new(f));
+    }
+
+    /// Removes the definition of the parser, releasing everything it refers to.
+    ///
+    /// # Panics
+    ///
+    /// If the parser is currently running.
+    #[inline]
+    pub fn clear(&self) {
+        self.0.borrow_mut().take();
+    }
+
+    /// Returns true if the parser has been defined.
+    #[inline]
+    pub fn is_defined(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    /// Applies the parser.
+    ///
+    /// # Panics
+    ///
+    /// If the parser has not been defined.
+    #[inline]
+    pub fn parse<'a>(&self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        let d = self.0.borrow();
+
+        (d.as_ref().expect("Lazy::parse: parser has not been defined"))(i)
+    }
+}
+
+impl<'p, I, T, E> Clone for Lazy<'p, I, T, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Lazy(self.0.clone())
+    }
+}
+
+impl<'p, I, T, E> Default for Lazy<'p, I, T, E> {
+    #[inline]
+    fn default() -> Self {
+        Lazy::new()
+    }
+}
+
+impl<'p, I, T, E> fmt::Debug for Lazy<'p, I, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy").field("defined", &self.is_defined()).finish()
+    }
+}
+
+/// Applies the parser ``p``, see `Lazy`.
+///
+/// # Panics
+///
+/// If ``p`` has not been defined.
+#[inline]
+pub fn lazy<'a, 'p, I, T, E>(i: Input<'a, I>, p: &Lazy<'p, I, T, E>) -> ParseResult<'a, I, T, E> {
+    p.parse(i)
+}
+
+#[cfg(test)]
+mod test {
+    use primitives::State;
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::IntoInner;
+    use parsers::{any, token};
+    use combinators::or;
+    use {Error, U8Result};
+
+    use super::*;
+
+    #[test]
+    fn fix_test() {
+        // Counts the number of leading 'a' characters
+        fn p(i: Input<u8>, n: i32) -> U8Result<i32> {
+            fix(i, |p, i| or(i, |i| token(i, b'a').then(p).map(|m| m + n), |i| i.ret(0)))
+        }
+
+        let p = |i| p(i, 1);
+
+        assert_eq!(p(new(END_OF_INPUT, b"aaab")).into_inner(), State::Data(new(END_OF_INPUT, b"b"), 3));
+        assert_eq!(p(new(END_OF_INPUT, b"b")).into_inner(), State::Data(new(END_OF_INPUT, b"b"), 0));
+        assert_eq!(p(new(DEFAULT, b"aa")).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn lazy_test() {
+        let even: Lazy<u8, i32, Error<u8>> = Lazy::new();
+        let odd  = Lazy::new();
+
+        assert!( ! even.is_defined());
+
+        // Alternating 'a' and 'b', returning the number of items
+        let (e, o) = (even.clone(), odd.clone());
+        even.define(move |i| or(i, |i| token(i, b'a').then(|i| lazy(i, &o)).map(|n| n + 1), |i| i.ret(0)));
+        odd.define(move |i| or(i, |i| token(i, b'b').then(|i| lazy(i, &e)).map(|n| n + 1), |i| i.ret(0)));
+
+        assert!(even.is_defined());
+        assert_eq!(even.parse(new(END_OF_INPUT, b"ababb")).into_inner(), State::Data(new(END_OF_INPUT, b"b"), 4));
+        assert_eq!(odd.parse(new(END_OF_INPUT, b"ba")).into_inner(), State::Data(new(END_OF_INPUT, b""), 2));
+
+        even.clear();
+
+        assert!( ! even.is_defined());
+
+        even.define(|i| any(i).map(|_| 7));
+
+        assert_eq!(lazy(new(DEFAULT, b"c"), &even).into_inner(), State::Data(new(DEFAULT, b""), 7));
+    }
+
+    #[test]
+    fn lazy_reuse() {
+        let p: Lazy<u8, Vec<u8>, Error<u8>> = Lazy::new();
+        let q = p.clone();
+
+        // Nested brackets, returning the innermost item
+        p.define(move |i| or(i, |i| token(i, b'[').then(|i| lazy(i, &q)).bind(|i, v| token(i, b']').map(|_| v)), |i| any(i).map(|c| vec![c])));
+
+        for s in &["[[a]]", "b"] {
+            // Each buffer only lives for one iteration
+            let buf = s.as_bytes().to_vec();
+
+            assert_eq!(p.parse(new(END_OF_INPUT, &buf)).into_inner(), State::Data(new(END_OF_INPUT, b""), vec![buf[buf.len() / 2]]));
+        }
+
+        p.clear();
+    }
+
+    #[test]
+    #[should_panic(expected = "parser has not been defined")]
+    fn lazy_undefined() {
+        let p: Lazy<u8, u8, ()> = Lazy::new();
+
+        let _ = p.parse(new(DEFAULT, b"a"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Imperative parsers operating on `&mut Input`.
+//!
+//! Instead of threading the `Input` through `bind` an imperative parser borrows it mutably and
+//! returns a plain `Result`, which allows for loops, early returns and the `?` operator:
+//!
+//! ```ignore
+//! FnMut(&mut Input<I>, ...) -> imperative::Result<T, E>;
+//! ```
+//!
+//! On success the input has been advanced past the parsed value and on error it is positioned
+//! where the error occurred. `run` applies a regular parser to a `&mut Input` and `lift` turns an
+//! imperative parser back into a regular one, so the two styles can be mixed freely.
+//!
+//! ```
+//! use chomp::{Input, U8Result, parse_only, token};
+//! use chomp::ascii::decimal;
+//! use chomp::imperative::{self, lift, opt, run};
+//!
+//! // A comma-separated list of numbers
+//! fn numbers(i: &mut Input<u8>) -> imperative::Result<Vec<u32>, chomp::Error<u8>> {
+//!     let mut v = vec![run(i, decimal)?];
+//!
+//!     while opt(i, |i| run(i, |i| token(i, b',')))?.is_some() {
+//!         v.push(run(i, decimal)?);
+//!     }
+//!
+//!     Ok(v)
+//! }
+//!
+//! fn parser(i: Input<u8>) -> U8Result<Vec<u32>> {
+//!     lift(i, numbers)
+//! }
+//!
+//! assert_eq!(parse_only(parser, b"1,22,333;"), Ok(vec![1, 22, 333]));
+//! ```
+
+use std::error;
+use std::fmt;
+use std::result;
+
+use {Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// Failure of an imperative parser.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Failure<E> {
+    /// The parser failed with an error, the input is positioned where it occurred.
+    Error(E),
+    /// The parser requires at least this many additional items of input.
+    Incomplete(usize),
+}
+
+impl<E> Failure<E> {
+    /// Converts the error using ``f``, keeping `Incomplete` as it is.
+    #[inline]
+    pub fn map<F, G>(self, f: F) -> Failure<G>
+      where F: FnOnce(E) -> G {
+        match self {
+            Failure::Error(e)      => Failure::Error(f(e)),
+            Failure::Incomplete(n) => Failure::Incomplete(n),
+        }
+    }
+}
+
+/// Allows `?` to be used on a plain `Result` inside of an imperative parser.
+impl<E> From<E> for Failure<E> {
+    #[inline]
+    fn from(e: E) -> Self {
+        Failure::Error(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Failure<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Error(ref e)  => write!(f, "{}", e),
+            Failure::Incomplete(n) => write!(f, "incomplete input, at least {} more items are required", n),
+        }
+    }
+}
+
+impl<E: error::Error> error::Error for Failure<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Failure::Error(ref e)  => e.source(),
+            Failure::Incomplete(_) => None,
+        }
+    }
+}
+
+/// Result of an imperative parser.
+pub type Result<T, E> = result::Result<T, Failure<E>>;
+
+/// Applies the parser ``f`` to the input, advancing it on success and moving it to the position
+/// of the error on failure. Incomplete leaves the input unchanged.
+///
+/// ```
+/// use chomp::{Error, any, take, token};
+/// use chomp::imperative::{Failure, run};
+/// use chomp::primitives::InputBuffer;
+/// use chomp::primitives::input::{new, END_OF_INPUT};
+///
+/// let mut i = new(END_OF_INPUT, b"ab");
+///
+/// assert_eq!(run(&mut i, any), Ok(b'a'));
+/// assert!(run(&mut i, |i| token(i, b'c')).is_err());
+/// assert_eq!(i.buffer(), b"b");
+/// assert_eq!(run(&mut i, |i| take(i, 2)), Err(Failure::<Error<u8>>::Incomplete(1)));
+/// ```
+#[inline]
+pub fn run<'a, I, T, E, F>(i: &mut Input<'a, I>, f: F) -> Result<T, E>
+  where T: 'a,
+        E: 'a,
+        F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    match f(i.clone()).into_inner() {
+        State::Data(b, t)    => {
+            *i = b;
+
+            Ok(t)
+        },
+        State::Error(b, e)   => {
+            *i = i.clone().replace(b);
+
+            Err(Failure::Error(e))
+        },
+        State::Incomplete(n) => Err(Failure::Incomplete(n)),
+    }
+}
+
+/// Applies the imperative parser ``f``, turning it into a regular parser.
+///
+/// ```
+/// use chomp::{Input, U8Result, parse_only, any};
+/// use chomp::imperative::{lift, run};
+///
+/// fn two(i: Input<u8>) -> U8Result<(u8, u8)> {
+///     lift(i, |i| Ok((run(i, any)?, run(i, any)?)))
+/// }
+///
+/// assert_eq!(parse_only(two, b"ab"), Ok((b'a', b'b')));
+/// ```
+#[inline]
+pub fn lift<'a, I, T, E, F>(i: Input<'a, I>, f: F) -> ParseResult<'a, I, T, E>
+  where T: 'a,
+        E: 'a,
+        F: FnOnce(&mut Input<'a, I>) -> Result<T, E> {
+    let mut i = i;
+
+    match f(&mut i) {
+        Ok(t)                       => i.ret(t),
+        Err(Failure::Error(e))      => i.err(e),
+        Err(Failure::Incomplete(n)) => i.incomplete(n),
+    }
+}
+
+/// Applies the imperative parser ``f``, restoring the input to where it started if ``f`` fails.
+///
+/// ```
+/// use chomp::{any, token};
+/// use chomp::imperative::{attempt, run};
+/// use chomp::primitives::InputBuffer;
+/// use chomp::primitives::input::{new, END_OF_INPUT};
+///
+/// let mut i = new(END_OF_INPUT, b"ab");
+///
+/// assert!(attempt(&mut i, |i| { run(i, any)?; run(i, |i| token(i, b'c')) }).is_err());
+/// assert_eq!(i.buffer(), b"ab");
+/// ```
+#[inline]
+pub fn attempt<'a, I, T, E, F>(i: &mut Input<'a, I>, f: F) -> Result<T, E>
+  where F: FnOnce(&mut Input<'a, I>) -> Result<T, E> {
+    let start = i.clone();
+
+    let r = f(i);
+
+    if r.is_err() {
+        *i = start;
+    }
+
+    r
+}
+
+/// Applies the imperative parser ``f``, returning `None` and restoring the input if it fails.
+///
+/// Like `combinators::option`, `Incomplete` is only propagated if this is not the last slice of
+/// input.
+///
+/// ```
+/// use chomp::token;
+/// use chomp::imperative::{opt, run};
+/// use chomp::primitives::InputBuffer;
+/// use chomp::primitives::input::{new, END_OF_INPUT};
+///
+/// let mut i = new(END_OF_INPUT, b"ab");
+///
+/// assert_eq!(opt(&mut i, |i| run(i, |i| token(i, b'b'))), Ok(None));
+/// assert_eq!(opt(&mut i, |i| run(i, |i| token(i, b'a'))), Ok(Some(b'a')));
+/// assert_eq!(i.buffer(), b"b");
+/// ```
+#[inline]
+pub fn opt<'a, I, T, E, F>(i: &mut Input<'a, I>, f: F) -> Result<Option<T>, E>
+  where F: FnOnce(&mut Input<'a, I>) -> Result<T, E> {
+    let start = i.clone();
+
+    match f(i) {
+        Ok(t)                       => Ok(Some(t)),
+        Err(Failure::Error(_))      => {
+            *i = start;
+
+            Ok(None)
+        },
+        Err(Failure::Incomplete(n)) => {
+            *i = start;
+
+            if i.is_last_slice() {
+                Ok(None)
+            } else {
+                Err(Failure::Incomplete(n))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::{InputBuffer, IntoInner, State};
+    use parsers::{any, take, token};
+
+    use super::*;
+
+    #[test]
+    fn run_test() {
+        let mut i = new(DEFAULT, b"abc");
+
+        assert_eq!(run(&mut i, any), Ok(b'a'));
+        assert_eq!(i.buffer(), b"bc");
+        assert_eq!(run(&mut i, |i| take(i, 1).map_err(|_| "take").then(|i| i.err::<(), _>("err"))), Err(Failure::Error("err")));
+        assert_eq!(i.buffer(), b"c");
+        assert_eq!(run(&mut i, |i| take(i, 3)), Err(Failure::Incomplete(2)));
+        assert_eq!(i.buffer(), b"c");
+    }
+
+    #[test]
+    fn lift_test() {
+        fn p<'a>(i: &mut Input<'a, u8>) -> Result<(u8, u8), &'static str> {
+            let a = run(i, any).map_err(|f| f.map(|_| "any"))?;
+
+            if a == b'x' {
+                return Err(Failure::Error("x"));
+            }
+
+            let b = run(i, |i| token(i, b'b')).map_err(|f| f.map(|_| "token"))?;
+
+            Ok((a, b))
+        }
+
+        assert_eq!(lift(new(DEFAULT, b"abc"), p).into_inner(), State::Data(new(DEFAULT, b"c"), (b'a', b'b')));
+        assert_eq!(lift(new(DEFAULT, b"xbc"), p).into_inner(), State::Error(&b"bc"[..], "x"));
+        assert_eq!(lift(new(DEFAULT, b"acc"), p).into_inner(), State::Error(&b"cc"[..], "token"));
+        assert_eq!(lift(new(DEFAULT, b"a"), p).into_inner(), State::Incomplete(1));
+    }
+
+    #[test]
+    fn attempt_test() {
+        let mut i = new(DEFAULT, b"abc");
+
+        assert_eq!(attempt(&mut i, |i| { run(i, any)?; run(i, |i| token(i, b'c')) }).map_err(|f| f.map(|_| ())), Err(Failure::Error(())));
+        assert_eq!(i.buffer(), b"abc");
+        assert_eq!(attempt(&mut i, |i| { run(i, any)?; run(i, |i| token(i, b'b')) }).map_err(|f| f.map(|_| ())), Ok(b'b'));
+        assert_eq!(i.buffer(), b"c");
+        assert_eq!(attempt(&mut i, |i| run(i, |i| take(i, 2))).map_err(|f| f.map(|_| ())), Err(Failure::Incomplete(1)));
+        assert_eq!(i.buffer(), b"c");
+    }
+
+    #[test]
+    fn opt_test() {
+        let mut i = new(DEFAULT, b"ab");
+
+        assert_eq!(opt(&mut i, |i| run(i, |i| token(i, b'b'))).map_err(|f| f.map(|_| ())), Ok(None));
+        assert_eq!(opt(&mut i, |i| run(i, |i| token(i, b'a'))).map_err(|f| f.map(|_| ())), Ok(Some(b'a')));
+        assert_eq!(opt(&mut i, |i| run(i, |i| take(i, 2))).map_err(|f| f.map(|_| ())), Err(Failure::Incomplete(1)));
+        assert_eq!(i.buffer(), b"b");
+
+        let mut i = new(END_OF_INPUT, b"b");
+
+        assert_eq!(opt(&mut i, |i| run(i, |i| take(i, 2))).map_err(|f| f.map(|_| ())), Ok(None));
+        assert_eq!(i.buffer(), b"b");
+    }
+
+    #[test]
+    fn question_mark() {
+        fn p<'a>(i: &mut Input<'a, u8>) -> Result<u32, &'static str> {
+            let c = run(i, any).map_err(|f| f.map(|_| "any"))?;
+            let d = (c as char).to_digit(10).ok_or("digit")?;
+
+            Ok(d)
+        }
+
+        assert_eq!(lift(new(DEFAULT, b"7"), p).into_inner(), State::Data(new(DEFAULT, b""), 7));
+        assert_eq!(lift(new(DEFAULT, b"a"), p).into_inner(), State::Error(&b""[..], "digit"));
+    }
+}
@@ -181,6 +181,14 @@
 #[macro_use]
 extern crate bitflags;
 extern crate conv;
+#[cfg(feature = "async")]
+extern crate futures_io;
+#[cfg(all(test, feature = "async"))]
+extern crate futures;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(all(unix, feature = "ring_buffer"))]
+extern crate libc;
 
 #[macro_use]
 mod macros;
@@ -192,6 +200,10 @@ pub mod ascii;
 pub mod buffer;
 pub mod parsers;
 pub mod combinators;
+pub mod imperative;
+pub mod parser;
+pub mod profile;
+pub mod trace;
 
 pub use combinators::{
     count,
@@ -226,6 +238,7 @@ pub use parsers::{
 };
 pub use parsers::Error;
 pub use input::Input;
+pub use parser::Parser;
 pub use parse::{
     ParseError,
     parse_only,
@@ -106,8 +106,10 @@
 /// ```text
 /// Block     ::= Statement* Expr
 /// Statement ::= Bind ';'
+///             | BindElse ';'
 ///             | Expr ';'
 /// Bind      ::= 'let' Var '=' Expr
+/// BindElse  ::= 'let' $pat '=' Expr "else" "err" $expr
 /// Var       ::= $pat
 ///             | $ident ':' $ty
 ///
@@ -122,6 +124,8 @@
 /// Term      ::= Ret
 ///             | Err
 ///             | '(' Expr ')'
+///             | If
+///             | Match
 ///             | Inline
 ///             | Named
 ///
@@ -130,6 +134,9 @@
 /// Err       ::= "err" Typed
 ///             | "err" $expr
 /// Typed     ::= '@' $ty ',' $ty ':' $expr
+/// If        ::= "if" $expr '{' Block '}' "else" (If | '{' Block '}')
+/// Match     ::= "match" $expr '{' Arm* '}'
+/// Arm       ::= $pat ('|' $pat)* ("if" $expr)? "=>" ('{' Block '}' ','? | Expr ',')
 /// Inline    ::= $ident "->" $expr
 /// Named     ::= $ident '(' ($expr ',')* (',')* ')'
 /// ```
@@ -165,6 +172,33 @@
 /// types can also be declared with `identifier: type` when necessary (eg. declare integer type
 /// used with the `decimal` parser).
 ///
+/// ### Bind with else
+///
+/// A refutable pattern can be bound by following the expression with `else err` and an error value.
+/// If the value does not match the pattern the parser fails with the error, positioned after the
+/// input consumed by the expression.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// # use chomp::{parse_only, Input, ParseResult, any};
+/// fn digit(i: Input<u8>) -> ParseResult<u8, Option<u32>, &'static str> {
+///     parse!{i;
+///         let c = s -> any(s).map_err(|_| "end of input");
+///         ret (c as char).to_digit(10)
+///     }
+/// }
+///
+/// let p = parser!{
+///     let Some(n) = digit() else err "expected a digit";
+///     ret n
+/// };
+///
+/// assert_eq!(parse_only(&p, b"7"), Ok(7));
+/// assert_eq!(parse_only(&p, b"x"), Err(chomp::ParseError::Error(b"", "expected a digit")));
+/// # }
+/// ```
+///
 /// ### Action
 ///
 /// An action is any parser-expression, ended with a semicolon. This will be executed and its
@@ -235,6 +269,39 @@
 /// Note that we only declare the success type (`u32` above) and leave out the type of the error
 /// (by using `_`) since that can be uniquely inferred.
 ///
+/// ### If and Match
+///
+/// `if`/`else` and `match` branch on plain Rust values, every branch being a parse-block of its own
+/// which can contain statements. All branches have to result in the same type which means that an
+/// `if` always needs an `else`. Match-arms which are not blocks are parser-expressions ending with a
+/// comma.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// # use chomp::{parse_only, any, token, string};
+/// let p = parser!{
+///     let c = any();
+///     if c == b'"' {
+///         let s = string(b"quoted");
+///         token(b'"');
+///         ret s
+///     } else {
+///         match c {
+///             b'a' | b'b' => string(b"lpha"),
+///             _           => string(b"other"),
+///         }
+///     }
+/// };
+///
+/// assert_eq!(parse_only(&p, b"\"quoted\""), Ok(&b"quoted"[..]));
+/// assert_eq!(parse_only(&p, b"blpha"), Ok(&b"lpha"[..]));
+/// # }
+/// ```
+///
+/// The condition and the matched expression cannot contain `>>`, `<|>` or `<*` outside of
+/// parentheses since these are treated as the operators of the surrounding parser-expression.
+///
 /// ### Inline
 ///
 /// An inline expression is essentially a closure where the parser state (`Input` type) is exposed.
@@ -439,6 +506,26 @@ macro_rules! __parse_internal_or {
     ($input:expr, $lhs:expr, $rhs:expr) => { $crate::combinators::or($input, $lhs, $rhs) };
 }
 
+/// Internal rule to trace a statement, separate macro so that tests can override it.
+///
+/// Wraps the statement in `trace::trace` when the `trace` feature is enabled.
+#[cfg(feature = "trace")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __parse_internal_trace {
+    ($input:expr; $($exp:tt)+) => { $crate::trace::trace($input, stringify!($($exp)*), |i| __parse_internal!{@EXPR(i;) $($exp)*}) };
+}
+
+/// Internal rule to trace a statement, separate macro so that tests can override it.
+///
+/// Wraps the statement in `trace::trace` when the `trace` feature is enabled.
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __parse_internal_trace {
+    ($input:expr; $($exp:tt)+) => { __parse_internal!{@EXPR($input;) $($exp)*} };
+}
+
 /// Actual implementation of the parse macro, hidden to make the documentation easier to read.
 ///
 /// Patterns starting with @ symbols are internal rules, used by other parts of the macro.
@@ -450,14 +537,20 @@ macro_rules! __parse_internal {
     // BIND ties an expression together with the following statement
     // The four versions are needed to allow the empty case (no tailing allowed on the empty
     // case), _, $pat and $ident:$ty.
-    ( @BIND(($input:expr ; _)                         $($exp:tt)+) )              => { __parse_internal!{@EXPR($input;) $($exp)* } };
-    ( @BIND(($input:expr ; _)                         $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal!{@EXPR($input;) $($exp)* }.bind(|i, _| __parse_internal!{i; $($tail)* }) };
-    ( @BIND(($input:expr ; $name:pat)                 $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal!{@EXPR($input;) $($exp)* }.bind(|i, $name| __parse_internal!{i; $($tail)* }) };
-    ( @BIND(($input:expr ; $name:ident : $name_ty:ty) $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal!{@EXPR($input;) $($exp)* }.bind(|i, $name : $name_ty| __parse_internal!{i; $($tail)* }) };
+    ( @BIND(($input:expr ; _)                         $($exp:tt)+) )              => { __parse_internal_trace!{$input; $($exp)* } };
+    ( @BIND(($input:expr ; _)                         $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal_trace!{$input; $($exp)* }.bind(|i, _| __parse_internal!{i; $($tail)* }) };
+    ( @BIND(($input:expr ; $name:pat)                 $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal_trace!{$input; $($exp)* }.bind(|i, $name| __parse_internal!{i; $($tail)* }) };
+    ( @BIND(($input:expr ; $name:ident : $name_ty:ty) $($exp:tt)+) $($tail:tt)+ ) => { __parse_internal_trace!{$input; $($exp)* }.bind(|i, $name : $name_ty| __parse_internal!{i; $($tail)* }) };
+
+    // BIND_ELSE is BIND for a refutable pattern, failing with the given error if it does not match
+    ( @BIND_ELSE(($input:expr ; $name:pat) ($($exp:tt)+) $err:expr) $($tail:tt)+ ) => { __parse_internal_trace!{$input; $($exp)* }.bind(|i, v| match v { $name => __parse_internal!{i; $($tail)* }, _ => i.err($err) }) };
+
 
     // Term ::= Ret
     //        | Err
     //        | '(' Expr ')'
+    //        | If
+    //        | Match
     //        | Inline
     //        | Named
     // Ret ::= "ret" Typed
@@ -470,11 +563,35 @@ macro_rules! __parse_internal {
     ( @TERM($input:expr) err $e:expr )                           => { $input.err($e) };
     // '(' Expr ')'
     ( @TERM($input:expr) ( $($inner:tt)* ) )                     => { __parse_internal!{@EXPR($input;) $($inner)*} };
+    // If ::= "if" $expr '{' Block '}' "else" (If | '{' Block '}')
+    ( @TERM($input:expr) if $($tail:tt)+ )                       => { __parse_internal!{@IF($input;) $($tail)+} };
+    // Match ::= "match" $expr '{' Arm* '}'
+    ( @TERM($input:expr) match $($tail:tt)+ )                    => { __parse_internal!{@MATCH($input;) $($tail)+} };
     // Inline ::= $ident "->" $expr
     ( @TERM($input:expr) $state:ident -> $e:expr )               => { { let $state = $input; $e } };
     // Named ::= $ident '(' ($expr ',')* (',')* ')'
     ( @TERM($input:expr) $func:ident ( $($param:expr),* $(,)*) ) => { $func($input, $($param),*) };
 
+    // IF collects the condition until the branches, the condition is kept as tokens to allow for
+    // `if let`
+    ( @IF($input:expr; $($cond:tt)+) { $($then:tt)* } else { $($other:tt)* } ) => { if $($cond)+ { __parse_internal!{$input; $($then)*} } else { __parse_internal!{$input; $($other)*} } };
+    ( @IF($input:expr; $($cond:tt)+) { $($then:tt)* } else if $($tail:tt)+ )   => { if $($cond)+ { __parse_internal!{$input; $($then)*} } else { __parse_internal!{@IF($input;) $($tail)+} } };
+    ( @IF($input:expr; $($cond:tt)*) $t:tt $($tail:tt)+ )                      => { __parse_internal!{@IF($input; $($cond)* $t) $($tail)+} };
+
+    // MATCH collects the scrutinee until the block of arms
+    ( @MATCH($input:expr; $($e:tt)+) { $($arms:tt)* } )                         => { __parse_internal!{@ARM($input; ($($e)+) []) $($arms)*} };
+    ( @MATCH($input:expr; $($e:tt)*) $t:tt $($tail:tt)+ )                       => { __parse_internal!{@MATCH($input; $($e)* $t) $($tail)+} };
+
+    // ARM accumulates the arms of a match, ARM_EXPR collects an arm without a block until ','
+    // Arm ::= $pat ('|' $pat)* ("if" $expr)? "=>" ('{' Block '}' | Expr) ','?
+    ( @ARM($input:expr; ($($e:tt)+) [$($acc:tt)*]) )                                                              => { match $($e)+ { $($acc)* } };
+    ( @ARM($input:expr; $e:tt [$($acc:tt)*]) $($p:pat)|+ $(if $guard:expr)* => { $($body:tt)* } , $($tail:tt)* ) => { __parse_internal!{@ARM($input; $e [$($acc)* $($p)|+ $(if $guard)* => { __parse_internal!{$input; $($body)*} },]) $($tail)*} };
+    ( @ARM($input:expr; $e:tt [$($acc:tt)*]) $($p:pat)|+ $(if $guard:expr)* => { $($body:tt)* } $($tail:tt)* )   => { __parse_internal!{@ARM($input; $e [$($acc)* $($p)|+ $(if $guard)* => { __parse_internal!{$input; $($body)*} },]) $($tail)*} };
+    ( @ARM($input:expr; $e:tt $acc:tt) $($p:pat)|+ $(if $guard:expr)* => $($tail:tt)+ )                           => { __parse_internal!{@ARM_EXPR($input; $e $acc ($($p)|+ $(if $guard)*)) $($tail)+} };
+    ( @ARM_EXPR($input:expr; $e:tt [$($acc:tt)*] ($($arm:tt)+) $($body:tt)+) , $($tail:tt)* )                    => { __parse_internal!{@ARM($input; $e [$($acc)* $($arm)+ => __parse_internal!{@EXPR($input;) $($body)+},]) $($tail)*} };
+    ( @ARM_EXPR($input:expr; $e:tt [$($acc:tt)*] ($($arm:tt)+) $($body:tt)+) )                                   => { __parse_internal!{@ARM($input; $e [$($acc)* $($arm)+ => __parse_internal!{@EXPR($input;) $($body)+},])} };
+    ( @ARM_EXPR($input:expr; $e:tt $acc:tt $arm:tt $($body:tt)*) $t:tt $($tail:tt)* )                           => { __parse_internal!{@ARM_EXPR($input; $e $acc $arm $($body)* $t) $($tail)*} };
+
     // EXPR groups by lowest priority item first which is then ">>"
     // Expr ::= ExprAlt
     ( @EXPR($input:expr; $($lhs:tt)*) )                          => { __parse_internal!{@EXPR_ALT($input;) $($lhs)*} };
@@ -539,31 +656,42 @@ macro_rules! __parse_internal {
     ( @EXPR_SKIP($input:expr; $($lhs:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt <* $($tail:tt)* )     => { __parse_internal!{@TERM($input) $($lhs)* $t1 $t2 $t3 $t4}.bind(|i, l| __parse_internal!{@EXPR_SKIP(i;) $($tail)*}.map(|_| l)) };
     ( @EXPR_SKIP($input:expr; $($lhs:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $($tail:tt)* ) => { __parse_internal!{@EXPR_SKIP($input; $($lhs)* $t1 $t2 $t3 $t4 $t5) $($tail)*} };
 
-    // STATEMENT eats and groups a full parse! expression until the next ;
+    // STATEMENT eats and groups a full parse! expression until the next ; or "else err"
+    ( @STATEMENT($args:tt $($data:tt)*) else err $err:expr ; $($tail:tt)* ) => { __parse_internal!{@BIND_ELSE($args ($($data)*) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) )                        => { __parse_internal!{@BIND($args $($data)*)} };
     ( @STATEMENT($args:tt $($data:tt)*) ; $($tail:tt)*)          => { __parse_internal!{@BIND($args $($data)*) $($tail)*} };
     // Recurse to eat until ; or end
     // Technically could just use a single pattern for this recursion:
     // ( @STATEMENT($args:tt $($data:tt)*) $t:tt $($tail:tt)* ) => { __parse_internal!{@STATEMENT($args $($data)* $t) $($tail)*} };
     // But to avoid the recursion limit somewhat we have explicit cases for up to 10 tokens before ; or end:
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt else err $err:expr ; $($tail:tt)* )                                                    => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt )                                                                                      => { __parse_internal!{@BIND($args $($data)* $t1)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt ; $($tail:tt)* )                                                                       => { __parse_internal!{@BIND($args $($data)* $t1) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt else err $err:expr ; $($tail:tt)* )                                             => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt )                                                                               => { __parse_internal!{@BIND($args $($data)* $t1 $t2)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt ; $($tail:tt)* )                                                                => { __parse_internal!{@BIND($args $($data)* $t1 $t2) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt else err $err:expr ; $($tail:tt)* )                                      => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt )                                                                        => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt ; $($tail:tt)* )                                                         => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt else err $err:expr ; $($tail:tt)* )                               => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt )                                                                 => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt ; $($tail:tt)* )                                                  => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt else err $err:expr ; $($tail:tt)* )                        => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt )                                                          => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt ; $($tail:tt)* )                                           => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt else err $err:expr ; $($tail:tt)* )                 => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5 $t6) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt )                                                   => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt ; $($tail:tt)* )                                    => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt else err $err:expr ; $($tail:tt)* )          => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt )                                            => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt ; $($tail:tt)* )                             => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt else err $err:expr ; $($tail:tt)* )   => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt )                                     => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt ; $($tail:tt)* )                      => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt else err $err:expr ; $($tail:tt)* ) => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt )                              => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt ; $($tail:tt)* )               => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9) $($tail)*} };
+    ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt else err $err:expr ; $($tail:tt)* ) => { __parse_internal!{@BIND_ELSE($args ($($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10) $err) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt )                      => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10)} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt ; $($tail:tt)* )       => { __parse_internal!{@BIND($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10) $($tail)*} };
     ( @STATEMENT($args:tt $($data:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt $t11:tt $($tail:tt)* ) => { __parse_internal!{@STATEMENT($args $($data)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10 $t11) $($tail)*} };
@@ -571,6 +699,7 @@ macro_rules! __parse_internal {
     // Public rules:
 
     // Statement ::= Bind ';'
+    //             | BindElse ';'
     //             | Expr ';'
     //           ::= 'let' $pat '=' Expr
     ( $input:expr ; let $name:pat = $($tail:tt)+ )                 => { __parse_internal!{@STATEMENT(($input; $name)) $($tail)+} };
@@ -602,6 +731,100 @@ macro_rules! parser {
     ( $($t:tt)* ) => { |i| parse!{i; $($t)* } }
 }
 
+/// Macro defining a set of named parsers which can refer to each other, sharing the same input and
+/// error types.
+///
+/// The block starts by declaring the input type and the error type of the grammar, followed by the
+/// rules. Each rule is expanded to a function taking an `Input<'a, I>` (followed by any parameters
+/// of the rule) and returning a `ParseResult<'a, I, T, E>`, the body of the rule is using the
+/// same syntax as `parse!`. The lifetime `'a` of the input can be used in the type of a rule.
+///
+/// ```
+/// # #[macro_use] extern crate chomp;
+/// # fn main() {
+/// use chomp::{parse_only, token, Error};
+/// use chomp::ascii::{decimal, skip_whitespace};
+///
+/// grammar!{
+///     type Input = u8;
+///     type Error = Error<u8>;
+///
+///     /// expr ::= term ('+' expr)?
+///     rule expr -> u32 = {
+///         let t = term();
+///         let e = (sym(b'+') >> expr()) <|> ret 0;
+///         ret t + e
+///     };
+///
+///     /// term ::= number | '(' expr ')'
+///     rule term -> u32 = decimal() <* skip_whitespace()
+///                      <|> (sym(b'(') >> expr() <* sym(b')'));
+///
+///     rule sym(c: u8) -> u8 = token(c) <* skip_whitespace();
+/// }
+///
+/// assert_eq!(parse_only(expr, b"1 + (2 + 3) + 4;"), Ok(10));
+/// # }
+/// ```
+///
+/// # Grammar
+///
+/// ```text
+/// Grammar ::= "type" "Input" '=' $ty ';' "type" "Error" '=' $ty ';' Rule*
+/// Rule    ::= $attr* $vis "rule" $ident Params? "->" $ty '=' Body
+/// Params  ::= '(' ($ident ':' $ty ',')* (',')* ')'
+/// Body    ::= '{' Block '}' ';'?
+///           | Expr ';'
+/// ```
+///
+/// `Block` and `Expr` are described in the documentation of `parse!`. Attributes, including doc
+/// comments, and visibility are placed on the generated function.
+///
+/// Every rule which is not wrapped in braces is collected by recursively invoking the macro, a
+/// large grammar can require the `recursion_limit` to be raised.
+#[macro_export]
+macro_rules! grammar {
+    ( type Input = $input:ty ; type Error = $err:ty ; $($rules:tt)* ) => { __grammar_internal!{@RULES($input, $err) $($rules)*} };
+}
+
+/// Actual implementation of the grammar macro, hidden to make the documentation easier to read.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __grammar_internal {
+    // FN creates the function of a single rule
+    ( @FN($input:ty, $err:ty) (($(#[$attr:meta])*) ($vis:vis) $name:ident ($($arg:ident : $arg_ty:ty),*) $t:ty) $($body:tt)* ) => {
+        $(#[$attr])*
+        $vis fn $name<'a>(i: $crate::Input<'a, $input>, $($arg : $arg_ty),*) -> $crate::ParseResult<'a, $input, $t, $err> {
+            parse!{i; $($body)*}
+        }
+    };
+
+    // Rule ::= $attr* $vis "rule" $ident Params? "->" $ty '=' Body
+    ( @RULES $args:tt ) => {};
+    //  Body ::= '{' Block '}' ';'?
+    ( @RULES $args:tt $(#[$attr:meta])* $vis:vis rule $name:ident ( $($arg:ident : $arg_ty:ty),* $(,)* ) -> $t:ty = { $($body:tt)* } ; $($tail:tt)* ) => { __grammar_internal!{@FN $args (($(#[$attr])*) ($vis) $name ($($arg : $arg_ty),*) $t) $($body)*} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @RULES $args:tt $(#[$attr:meta])* $vis:vis rule $name:ident ( $($arg:ident : $arg_ty:ty),* $(,)* ) -> $t:ty = { $($body:tt)* } $($tail:tt)* )   => { __grammar_internal!{@FN $args (($(#[$attr])*) ($vis) $name ($($arg : $arg_ty),*) $t) $($body)*} __grammar_internal!{@RULES $args $($tail)*} };
+    //        | Expr ';'
+    ( @RULES $args:tt $(#[$attr:meta])* $vis:vis rule $name:ident ( $($arg:ident : $arg_ty:ty),* $(,)* ) -> $t:ty = $($tail:tt)+ )                      => { __grammar_internal!{@EXPR($args (($(#[$attr])*) ($vis) $name ($($arg : $arg_ty),*) $t)) $($tail)+} };
+    // Params are optional
+    ( @RULES $args:tt $(#[$attr:meta])* $vis:vis rule $name:ident -> $($tail:tt)+ )                                                                   => { __grammar_internal!{@RULES $args $(#[$attr])* $vis rule $name() -> $($tail)+} };
+
+    // EXPR eats an expression until the next ;
+    // Unrolled to avoid the recursion limit somewhat, see STATEMENT in __parse_internal:
+    ( @EXPR($args:tt $head:tt $($body:tt)*) ; $($tail:tt)* )                                                                              => { __grammar_internal!{@FN $args $head $($body)*} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt ; $($tail:tt)* )                                                                       => { __grammar_internal!{@FN $args $head $($body)* $t1} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt ; $($tail:tt)* )                                                                => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt ; $($tail:tt)* )                                                         => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt ; $($tail:tt)* )                                                  => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt ; $($tail:tt)* )                                           => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt ; $($tail:tt)* )                                    => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt ; $($tail:tt)* )                             => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6 $t7} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt ; $($tail:tt)* )                      => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt ; $($tail:tt)* )               => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt ; $($tail:tt)* )       => { __grammar_internal!{@FN $args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10} __grammar_internal!{@RULES $args $($tail)*} };
+    ( @EXPR($args:tt $head:tt $($body:tt)*) $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $t8:tt $t9:tt $t10:tt $t11:tt $($tail:tt)* ) => { __grammar_internal!{@EXPR($args $head $($body)* $t1 $t2 $t3 $t4 $t5 $t6 $t7 $t8 $t9 $t10 $t11) $($tail)*} };
+}
+
 #[cfg(test)]
 mod test {
     /// Override the or-combinator used by parse! to make it possible to use the simplified
@@ -619,6 +842,12 @@ mod test {
         };
     }
 
+    /// Override the statement tracing used by parse! as the simplified test-types cannot be
+    /// traced.
+    macro_rules! __parse_internal_trace {
+        ($input:expr; $($exp:tt)+) => { __parse_internal!{@EXPR($input;) $($exp)*} };
+    }
+
     /// Simplified implementation of the emulated monad using linear types.
     #[derive(Debug, Eq, PartialEq)]
     struct Input(i64);
@@ -1570,4 +1799,188 @@ mod test {
 
         assert_eq!(r, Data::Value(321, 2));
     }
+
+    #[test]
+    fn if_else() {
+        fn a(i: Input) -> Data<u32, ()> {
+            Data::Value(i.0 + 1, 2)
+        }
+
+        fn b(i: Input) -> Data<u32, ()> {
+            Data::Value(i.0 + 2, 3)
+        }
+
+        let r = parse!{Input(123);
+            let n = a();
+            if n > 2 { b() } else { a(); b() }
+        };
+
+        assert_eq!(r, Data::Value(127, 3));
+
+        let r = parse!{Input(123);
+            let n = a();
+            if n == 0 { b() } else if n == 2 { let m = a(); ret m + 10 } else { b() }
+        };
+
+        assert_eq!(r, Data::Value(125, 12));
+    }
+
+    #[test]
+    fn if_let() {
+        fn a(i: Input) -> Data<Option<u32>, ()> {
+            Data::Value(i.0 + 1, Some(2))
+        }
+
+        let r: Data<_, ()> = parse!{Input(123);
+            let o = a();
+            let n = if let Some(n) = o { ret n } else { err () };
+            ret n * 2
+        };
+
+        assert_eq!(r, Data::Value(124, 4));
+    }
+
+    #[test]
+    fn if_operators() {
+        fn a(i: Input) -> Data<u32, &'static str> {
+            Data::Error(i.0 + 1, "a")
+        }
+
+        fn b(i: Input) -> Data<u32, &'static str> {
+            Data::Value(i.0 + 2, 2)
+        }
+
+        let c = false;
+        let r = parse!{Input(123); if c { b() } else { a() } <|> b()};
+
+        assert_eq!(r, Data::Value(125, 2));
+
+        let r = parse!{Input(123); ret 3 >> if c { ret 1 } else { a() }};
+
+        assert_eq!(r, Data::Error(124, "a"));
+    }
+
+    #[test]
+    fn match_arms() {
+        fn a(i: Input) -> Data<u32, ()> {
+            Data::Value(i.0 + 1, 2)
+        }
+
+        fn b(i: Input) -> Data<u32, ()> {
+            Data::Value(i.0 + 2, 3)
+        }
+
+        let p = |i, n: u32| parse!{i;
+            match n {
+                0 => b(),
+                1 | 2 => a() >> b(),
+                x if x > 10 => { a(); ret x }
+                _ => err ()
+            }
+        };
+
+        assert_eq!(p(Input(123), 0), Data::Value(125, 3));
+        assert_eq!(p(Input(123), 2), Data::Value(126, 3));
+        assert_eq!(p(Input(123), 11), Data::Value(124, 11));
+        assert_eq!(p(Input(123), 5), Data::Error(123, ()));
+    }
+
+    #[test]
+    fn match_statement() {
+        fn a(i: Input) -> Data<u32, ()> {
+            Data::Value(i.0 + 1, 2)
+        }
+
+        let r: Data<_, ()> = parse!{Input(123);
+            let n = a();
+            let m = match n { 2 => { ret 5 }, _ => a() };
+            match m { 5 => a(), _ => ret 0 };
+            ret n + m
+        };
+
+        assert_eq!(r, Data::Value(125, 7));
+    }
+
+    #[test]
+    fn bind_else() {
+        fn a(i: Input, o: Option<u32>) -> Data<Option<u32>, &'static str> {
+            Data::Value(i.0 + 1, o)
+        }
+
+        let r = parse!{Input(123);
+            let Some(n) = a(Some(2)) else err "none";
+            ret n + 1
+        };
+
+        assert_eq!(r, Data::Value(124, 3));
+
+        let r: Data<u32, _> = parse!{Input(123);
+            let Some(n) = a(None) else err "none";
+            ret n + 1
+        };
+
+        assert_eq!(r, Data::Error(124, "none"));
+
+        let r = parse!{Input(123);
+            let (1, Some(n)) = (i -> a(i, Some(2)).map(|o| (1, o))) else err "none";
+            ret n
+        };
+
+        assert_eq!(r, Data::Value(124, 2));
+    }
+}
+
+#[cfg(test)]
+mod grammar_test {
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::{IntoInner, State};
+    use parsers::{Error, any, token};
+
+    mod rules {
+        use parsers::{Error, take_while1, token};
+
+        grammar!{
+            type Input = u8;
+            type Error = Error<u8>;
+
+            /// A non-empty list of words separated by a single character
+            pub rule words(sep: u8,) -> Vec<&'a [u8]> = {
+                let w = word();
+                let r = (token(sep) >> words(sep)) <|> ret Vec::new();
+                ret {
+                    let mut r = r;
+                    r.insert(0, w);
+                    r
+                }
+            }
+
+            rule word -> &'a [u8] = take_while1(|c| c != b',' && c != b';' && c != b' ' && c != b'.');
+        }
+    }
+
+    grammar!{
+        type Input = u8;
+        type Error = Error<u8>;
+
+        rule quoted -> u8 = token(b'"') >> any() <* token(b'"') <* token(b';');
+        rule alt -> usize = {
+            let c = any();
+            match c {
+                b'q' => quoted() >> ret 1,
+                b'w' => { let ws = i -> rules::words(i, b','); ret ws.len() }
+                _    => err Error::new(),
+            }
+        };
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(rules::words(new(END_OF_INPUT, b"a,bc,d;"), b',').into_inner(), State::Data(new(END_OF_INPUT, b";"), vec![&b"a"[..], &b"bc"[..], &b"d"[..]]));
+        assert_eq!(rules::words(new(END_OF_INPUT, b"a bc;"), b' ').into_inner(), State::Data(new(END_OF_INPUT, b";"), vec![&b"a"[..], &b"bc"[..]]));
+        assert_eq!(quoted(new(DEFAULT, b"\"a\";")).into_inner(), State::Data(new(DEFAULT, b""), b'a'));
+        assert_eq!(quoted(new(END_OF_INPUT, b"\"a\".")).into_inner(), token(new(END_OF_INPUT, b"."), b';').into_inner());
+        assert_eq!(alt(new(END_OF_INPUT, b"q\"a\";")).into_inner(), State::Data(new(END_OF_INPUT, b""), 1));
+        assert_eq!(alt(new(END_OF_INPUT, b"wa,b.")).into_inner(), State::Data(new(END_OF_INPUT, b"."), 2));
+        assert_eq!(alt(new(END_OF_INPUT, b"x")).into_inner(), State::Error(&b""[..], Error::new()));
+    }
 }
@@ -0,0 +1,433 @@
+//! The `Parser` trait, allowing parsers to be composed using methods and stored as values.
+//!
+//! Any function or closure of the shape `FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E>`
+//! implements `Parser`, which means that existing parsers like `any` can be used directly while
+//! parsers taking extra parameters like `token` are used through a closure. Parsers of different
+//! types can be stored together after type-erasing them using `Parser::boxed`.
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use chomp::{Input, parse_only, token, take_while1};
+//! use chomp::parser::{BoxedParser, Parser};
+//!
+//! let mut fields: HashMap<&str, BoxedParser<u8, Vec<&[u8]>, _>> = HashMap::new();
+//!
+//! fields.insert("words", (|i| take_while1(i, |c| c != b' ' && c != b','))
+//!     .sep_by(|i: Input<'static, u8>| token(i, b' ')).boxed());
+//! fields.insert("list", (|i| take_while1(i, |c| c != b' ' && c != b','))
+//!     .sep_by(|i: Input<'static, u8>| token(i, b',')).boxed());
+//!
+//! let words = fields.get_mut("words").unwrap();
+//!
+//! assert_eq!(parse_only(|i| words.parse(i), b"a b,c"), Ok(vec![&b"a"[..], &b"b"[..]]));
+//!
+//! let list = fields.get_mut("list").unwrap();
+//!
+//! assert_eq!(parse_only(|i| list.parse(i), b"a,b c"), Ok(vec![&b"a"[..], &b"b"[..]]));
+//! ```
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use {Input, ParseResult};
+use combinators;
+
+/// A parser, consuming input and producing either a value or an error.
+///
+/// Implemented for all functions and closures of the shape
+/// `FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E>`.
+///
+/// ```
+/// use chomp::{parse_only, any, token};
+/// use chomp::parser::Parser;
+///
+/// let p = any.map(|c| c + 1).then(|i| token(i, b'b'));
+///
+/// assert_eq!(parse_only(p.into_fn(), b"ab"), Ok(b'b'));
+/// ```
+pub trait Parser<'a, I: 'a> {
+    /// The value produced by the parser on success.
+    type Output: 'a;
+    /// The error produced by the parser on failure.
+    type Error: 'a;
+
+    /// Runs the parser on the input ``i``.
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, Self::Output, Self::Error>;
+
+    /// Applies the function ``f`` on the value produced by this parser.
+    #[inline]
+    fn map<F, U>(self, f: F) -> Map<Self, F>
+      where Self: Sized,
+            F: FnMut(Self::Output) -> U {
+        Map { p: self, f }
+    }
+
+    /// Runs this parser followed by the parser ``p``, yielding the value of ``p``.
+    ///
+    /// The error of this parser is converted into the error of ``p`` using the ``From`` trait.
+    #[inline]
+    fn then<P>(self, p: P) -> Then<Self, P>
+      where Self: Sized,
+            P: Parser<'a, I>,
+            P::Error: From<Self::Error> {
+        Then { p: self, q: p }
+    }
+
+    /// Tries this parser and if it fails tries ``p``, see ``combinators::or``.
+    #[inline]
+    fn or<P>(self, p: P) -> Or<Self, P>
+      where Self: Sized,
+            P: Parser<'a, I, Output=Self::Output, Error=Self::Error> {
+        Or { p: self, q: p }
+    }
+
+    /// Applies this parser until it no longer matches, collecting all values into ``T``, see
+    /// ``combinators::many``.
+    #[inline]
+    fn many<T>(self) -> Many<Self, T>
+      where Self: Sized,
+            I: Copy,
+            T: FromIterator<Self::Output> {
+        Many { p: self, t: PhantomData }
+    }
+
+    /// Applies this parser zero or more times separated by ``sep``, collecting all values into
+    /// ``T``, see ``combinators::sep_by``.
+    #[inline]
+    fn sep_by<T, S>(self, sep: S) -> SepBy<Self, S, T>
+      where Self: Sized,
+            I: Copy,
+            S: Parser<'a, I>,
+            Self::Error: From<S::Error>,
+            T: FromIterator<Self::Output> {
+        SepBy { p: self, sep, t: PhantomData }
+    }
+
+    /// Type-erases this parser into a ``BoxedParser``.
+    #[inline]
+    fn boxed(self) -> BoxedParser<'a, I, Self::Output, Self::Error>
+      where Self: Sized + 'a {
+        BoxedParser(Box::new(self))
+    }
+
+    /// Converts this parser into a closure, for use with functions expecting ``FnOnce`` or
+    /// ``FnMut`` parsers.
+    #[inline]
+    fn into_fn(mut self) -> impl FnMut(Input<'a, I>) -> ParseResult<'a, I, Self::Output, Self::Error>
+      where Self: Sized {
+        move |i| self.parse(i)
+    }
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a, F> Parser<'a, I> for F
+  where F: FnMut(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    type Output = T;
+    type Error  = E;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        self(i)
+    }
+}
+
+/// Parser created by ``Parser::map``.
+#[derive(Clone, Debug)]
+pub struct Map<P, F> {
+    p: P,
+    f: F,
+}
+
+impl<'a, I: 'a, U: 'a, P, F> Parser<'a, I> for Map<P, F>
+  where P: Parser<'a, I>,
+        F: FnMut(P::Output) -> U {
+    type Output = U;
+    type Error  = P::Error;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, U, P::Error> {
+        self.p.parse(i).map(&mut self.f)
+    }
+}
+
+/// Parser created by ``Parser::then``.
+#[derive(Clone, Debug)]
+pub struct Then<P, Q> {
+    p: P,
+    q: Q,
+}
+
+impl<'a, I: 'a, P, Q> Parser<'a, I> for Then<P, Q>
+  where P: Parser<'a, I>,
+        Q: Parser<'a, I>,
+        Q::Error: From<P::Error> {
+    type Output = Q::Output;
+    type Error  = Q::Error;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, Q::Output, Q::Error> {
+        let q = &mut self.q;
+
+        self.p.parse(i).then(|i| q.parse(i))
+    }
+}
+
+/// Parser created by ``Parser::or``.
+#[derive(Clone, Debug)]
+pub struct Or<P, Q> {
+    p: P,
+    q: Q,
+}
+
+impl<'a, I: 'a, P, Q> Parser<'a, I> for Or<P, Q>
+  where P: Parser<'a, I>,
+        Q: Parser<'a, I, Output=P::Output, Error=P::Error> {
+    type Output = P::Output;
+    type Error  = P::Error;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, P::Output, P::Error> {
+        let (p, q) = (&mut self.p, &mut self.q);
+
+        combinators::or(i, |i| p.parse(i), |i| q.parse(i))
+    }
+}
+
+/// Parser created by ``Parser::many``.
+pub struct Many<P, T> {
+    p: P,
+    t: PhantomData<fn() -> T>,
+}
+
+impl<'a, I: 'a + Copy, T: 'a, P> Parser<'a, I> for Many<P, T>
+  where P: Parser<'a, I>,
+        T: FromIterator<P::Output> {
+    type Output = T;
+    type Error  = P::Error;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, P::Error> {
+        let p = &mut self.p;
+
+        combinators::many(i, |i| p.parse(i))
+    }
+}
+
+impl<P: Clone, T> Clone for Many<P, T> {
+    fn clone(&self) -> Self {
+        Many { p: self.p.clone(), t: PhantomData }
+    }
+}
+
+impl<P: fmt::Debug, T> fmt::Debug for Many<P, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Many").field("p", &self.p).finish()
+    }
+}
+
+/// Parser created by ``Parser::sep_by``.
+pub struct SepBy<P, S, T> {
+    p:   P,
+    sep: S,
+    t:   PhantomData<fn() -> T>,
+}
+
+impl<'a, I: 'a + Copy, T: 'a, P, S> Parser<'a, I> for SepBy<P, S, T>
+  where P: Parser<'a, I>,
+        S: Parser<'a, I>,
+        P::Error: From<S::Error>,
+        T: FromIterator<P::Output> {
+    type Output = T;
+    type Error  = P::Error;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, P::Error> {
+        let (p, sep) = (&mut self.p, &mut self.sep);
+
+        combinators::sep_by(i, |i| p.parse(i), |i| sep.parse(i))
+    }
+}
+
+impl<P: Clone, S: Clone, T> Clone for SepBy<P, S, T> {
+    fn clone(&self) -> Self {
+        SepBy { p: self.p.clone(), sep: self.sep.clone(), t: PhantomData }
+    }
+}
+
+impl<P: fmt::Debug, S: fmt::Debug, T> fmt::Debug for SepBy<P, S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SepBy").field("p", &self.p).field("sep", &self.sep).finish()
+    }
+}
+
+/// A type-erased parser, created by ``Parser::boxed``.
+///
+/// Allows parsers of different types to be stored together, for example in a ``Vec`` or a
+/// ``HashMap`` when building a grammar at runtime.
+///
+/// # Note
+///
+/// The parser is tied to the single input lifetime ``'a``, so it can only be used on input which
+/// lives at least as long as the parser, it cannot be reused on a new buffer for each call to
+/// `buffer::Source::parse`. Parsers producing owned values can instead be stored as an
+/// ``OwnedParser`` which accepts input of any lifetime.
+pub struct BoxedParser<'a, I: 'a, T: 'a, E: 'a>(Box<dyn Parser<'a, I, Output=T, Error=E> + 'a>);
+
+impl<'a, I: 'a, T: 'a, E: 'a> Parser<'a, I> for BoxedParser<'a, I, T, E> {
+    type Output = T;
+    type Error  = E;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        self.0.parse(i)
+    }
+
+    #[inline]
+    fn boxed(self) -> BoxedParser<'a, I, T, E> {
+        self
+    }
+}
+
+impl<'a, I: 'a, T: 'a, E: 'a> fmt::Debug for BoxedParser<'a, I, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BoxedParser")
+    }
+}
+
+/// A type-erased parser producing owned values, which can be reused on input of any lifetime.
+///
+/// Unlike ``BoxedParser`` this is not tied to the lifetime of one input, which allows a grammar
+/// built at runtime to be stored and applied to successive buffers. The parser needs to accept
+/// input of any lifetime, which is the case for parser functions and the adaptors of ``Parser``
+/// applied to them.
+///
+/// ```
+/// use chomp::{parse_only, any};
+/// use chomp::ascii::decimal;
+/// use chomp::parser::{OwnedParser, Parser};
+///
+/// let mut ps: Vec<OwnedParser<u8, u32, _>> = vec![
+///     OwnedParser::new(decimal),
+///     OwnedParser::new(any.map(|c| c as u32)),
+/// ];
+///
+/// for line in &["12", "3"] {
+///     // A new buffer for every line
+///     let buf = line.as_bytes().to_vec();
+///
+///     let r: Vec<_> = ps.iter_mut().map(|p| parse_only(|i| p.parse(i), &buf)).collect();
+///
+///     assert_eq!(r[1], Ok(buf[0] as u32));
+/// }
+/// ```
+pub struct OwnedParser<I: 'static, T: 'static, E: 'static>(Box<dyn for<'x> Parser<'x, I, Output=T, Error=E>>);
+
+impl<I, T, E> OwnedParser<I, T, E> {
+    /// Type-erases the parser ``p``.
+    #[inline]
+    pub fn new<P>(p: P) -> Self
+      where P: for<'x> Parser<'x, I, Output=T, Error=E> + 'static {
+        OwnedParser(Box::new(p))
+    }
+}
+
+impl<'a, I, T, E> Parser<'a, I> for OwnedParser<I, T, E> {
+    type Output = T;
+    type Error  = E;
+
+    #[inline]
+    fn parse(&mut self, i: Input<'a, I>) -> ParseResult<'a, I, T, E> {
+        self.0.parse(i)
+    }
+}
+
+impl<I, T, E> fmt::Debug for OwnedParser<I, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OwnedParser")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use primitives::State;
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::IntoInner;
+    use parsers::{Error, any, take_while1, token};
+
+    use super::*;
+
+    #[test]
+    fn fn_parser() {
+        assert_eq!(any.parse(new(DEFAULT, b"abc")).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!((|i| token(i, b'a')).parse(new(DEFAULT, b"abc")).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+    }
+
+    #[test]
+    fn map_then() {
+        let mut p = any.map(|c| c + 1);
+
+        assert_eq!(p.parse(new(DEFAULT, b"abc")).into_inner(), State::Data(new(DEFAULT, b"bc"), b'b'));
+        assert_eq!(p.parse(new(DEFAULT, b"bc")).into_inner(), State::Data(new(DEFAULT, b"c"), b'c'));
+
+        let mut p = (|i| token(i, b'a')).then(any);
+
+        assert_eq!(p.parse(new(DEFAULT, b"abc")).into_inner(), State::Data(new(DEFAULT, b"c"), b'b'));
+        assert_eq!(p.parse(new(DEFAULT, b"bc")).into_inner(), token(new(DEFAULT, b"bc"), b'a').into_inner());
+    }
+
+    #[test]
+    fn or() {
+        let mut p = (|i| token(i, b'a')).or(|i| token(i, b'b'));
+
+        assert_eq!(p.parse(new(DEFAULT, b"abc")).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!(p.parse(new(DEFAULT, b"bc")).into_inner(), State::Data(new(DEFAULT, b"c"), b'b'));
+        assert_eq!(p.parse(new(DEFAULT, b"c")).into_inner(), token(new(DEFAULT, b"c"), b'b').into_inner());
+    }
+
+    #[test]
+    fn many_sep_by() {
+        let mut p = (|i| token(i, b'a')).many::<Vec<_>>();
+
+        assert_eq!(p.parse(new(END_OF_INPUT, b"aab")).into_inner(), State::Data(new(END_OF_INPUT, b"b"), vec![b'a', b'a']));
+        assert_eq!(p.parse(new(DEFAULT, b"aa")).into_inner(), State::Incomplete(1));
+
+        let mut p = (|i| take_while1(i, |c| c != b',')).sep_by::<Vec<_>, _>(|i| token(i, b','));
+
+        assert_eq!(p.parse(new(END_OF_INPUT, b"ab,c")).into_inner(), State::Data(new(END_OF_INPUT, b""), vec![&b"ab"[..], &b"c"[..]]));
+    }
+
+    #[test]
+    fn boxed() {
+        let mut ps: Vec<BoxedParser<u8, u8, _>> = vec![
+            any.boxed(),
+            (|i| token(i, b'a')).boxed(),
+            any.map(|c| c + 1).boxed(),
+        ];
+
+        let r: Vec<_> = ps.iter_mut().map(|p| p.parse(new(DEFAULT, b"abc")).into_inner()).collect();
+
+        assert_eq!(r, vec![
+            State::Data(new(DEFAULT, b"bc"), b'a'),
+            State::Data(new(DEFAULT, b"bc"), b'a'),
+            State::Data(new(DEFAULT, b"bc"), b'b'),
+        ]);
+    }
+
+    #[test]
+    fn owned_reuse() {
+        // Closures are not generic over the input lifetime, functions are
+        fn a(i: Input<u8>) -> ParseResult<u8, u8, Error<u8>> {
+            token(i, b'a')
+        }
+
+        let mut p: OwnedParser<u8, Vec<u8>, _> = OwnedParser::new(a.or(any).many());
+
+        for s in &["aab", "ba"] {
+            // Each buffer only lives for one iteration
+            let buf = s.as_bytes().to_vec();
+
+            assert_eq!(p.parse(new(END_OF_INPUT, &buf)).into_inner(), State::Data(new(END_OF_INPUT, b""), buf.clone()));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Profiling of named parsers.
+//!
+//! With the `profile` feature enabled the `profile` combinator records how often a parser is
+//! invoked, how it completes and how much input it scans and consumes, together with the time
+//! spent in it. The counters are accumulated per thread and are obtained as a `Report` sorted by
+//! name, which makes reports of different versions of a grammar easy to compare.
+//!
+//! Without the `profile` feature `profile` only applies the parser and nothing is recorded.
+//!
+//! ```
+//! use chomp::{parse_only, token, take_while1};
+//! use chomp::combinators::or;
+//! use chomp::profile::{profile, report, reset};
+//!
+//! let p = |i| or(i,
+//!     |i| profile(i, "greeting", |i| take_while1(i, |c| c != b' ').bind(|i, w| token(i, b'!').map(|_| w))),
+//!     |i| profile(i, "word", |i| take_while1(i, |c| c != b' ')));
+//!
+//! reset();
+//!
+//! assert_eq!(parse_only(&p, b"hello world"), Ok(&b"hello"[..]));
+//!
+//! let r = report();
+//!
+//! if cfg!(feature = "profile") {
+//!     let greeting = r.get("greeting").unwrap();
+//!
+//!     assert_eq!(greeting.calls, 1);
+//!     assert_eq!(greeting.errors, 1);
+//!     assert_eq!(greeting.scanned, 5);
+//!
+//!     print!("{}", r);
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use {Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// Counters for a single named parser.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Stats {
+    /// Number of times the parser has been invoked.
+    pub calls:      u64,
+    /// Number of times the parser succeeded.
+    pub data:       u64,
+    /// Number of times the parser failed, causing the caller to backtrack or fail.
+    pub errors:     u64,
+    /// Number of times the parser requested more input.
+    pub incomplete: u64,
+    /// Total number of items consumed by successful invocations.
+    pub consumed:   u64,
+    /// Total number of items scanned by failing invocations before reaching the error.
+    pub scanned:    u64,
+    /// Total time spent in the parser, including any nested profiled parsers.
+    pub time:       Duration,
+}
+
+/// Sorted report of the counters of all profiled parsers, see `report`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report(Vec<(String, Stats)>);
+
+impl Report {
+    /// The counters of all profiled parsers, sorted by name.
+    pub fn entries(&self) -> &[(String, Stats)] {
+        &self.0
+    }
+
+    /// The counters of the parser with the given name, if it has been invoked.
+    pub fn get(&self, name: &str) -> Option<&Stats> {
+        self.0.binary_search_by(|e| e.0.as_str().cmp(name)).ok().map(|n| &self.0[n].1)
+    }
+}
+
+/// One line per parser, timings are written in microseconds.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let w = self.0.iter().map(|e| e.0.len()).max().unwrap_or(0).max(4);
+
+        writeln!(f, "{:w$} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12}",
+                 "rule", "calls", "data", "errors", "incomplete", "consumed", "scanned", "time (us)", w = w)?;
+
+        for (name, s) in &self.0 {
+            writeln!(f, "{:w$} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12}",
+                     name, s.calls, s.data, s.errors, s.incomplete, s.consumed, s.scanned,
+                     s.time.as_micros(), w = w)?;
+        }
+
+        Ok(())
+    }
+}
+
+thread_local!(static REGISTRY: RefCell<BTreeMap<String, Stats>> = const { RefCell::new(BTreeMap::new()) });
+
+/// Returns the counters of all parsers profiled on the current thread.
+pub fn report() -> Report {
+    REGISTRY.with(|r| Report(r.borrow().iter().map(|(k, v)| (k.clone(), *v)).collect()))
+}
+
+/// Clears the counters of all parsers profiled on the current thread.
+pub fn reset() {
+    REGISTRY.with(|r| r.borrow_mut().clear())
+}
+
+/// Applies the parser ``f``, recording its counters under the given name when the `profile`
+/// feature is enabled.
+///
+/// ```
+/// use chomp::{parse_only, any};
+/// use chomp::profile::profile;
+///
+/// assert_eq!(parse_only(|i| profile(i, "any", any), b"a"), Ok(b'a'));
+/// ```
+#[inline(always)]
+pub fn profile<'a, I, T, E, F>(i: Input<'a, I>, name: &str, f: F) -> ParseResult<'a, I, T, E>
+  where F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    if cfg!(feature = "profile") {
+        profiled(i, name, f)
+    } else {
+        f(i)
+    }
+}
+
+/// Implementation of `profile` when the `profile` feature is enabled.
+fn profiled<'a, I, T, E, F>(i: Input<'a, I>, name: &str, f: F) -> ParseResult<'a, I, T, E>
+  where F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    let len   = i.buffer().len();
+    let start = Instant::now();
+    let r     = f(i.clone()).into_inner();
+    let time  = start.elapsed();
+
+    REGISTRY.with(|reg| {
+        let mut reg = reg.borrow_mut();
+
+        if !reg.contains_key(name) {
+            reg.insert(name.to_owned(), Stats::default());
+        }
+
+        let s = reg.get_mut(name).expect("profile: entry was just inserted");
+
+        s.calls += 1;
+        s.time  += time;
+
+        match r {
+            State::Data(ref b, _)  => {
+                s.data     += 1;
+                s.consumed += (len - b.buffer().len()) as u64;
+            },
+            State::Error(b, _)     => {
+                s.errors  += 1;
+                s.scanned += len.saturating_sub(b.len()) as u64;
+            },
+            State::Incomplete(_)   => s.incomplete += 1,
+        }
+    });
+
+    match r {
+        State::Data(b, t)    => b.ret(t),
+        State::Error(b, e)   => i.replace(b).err(e),
+        State::Incomplete(n) => i.incomplete(n),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use primitives::State;
+    use primitives::input::{new, DEFAULT};
+    use primitives::IntoInner;
+    use parsers::{any, take, token};
+
+    use super::*;
+
+    #[test]
+    fn profile_result() {
+        assert_eq!(profile(new(DEFAULT, b"abc"), "any", any).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!(profile(new(DEFAULT, b"abc"), "err", |i| take(i, 1).map_err(|_| "take").then(|i| i.err::<(), _>("err"))).into_inner(), State::Error(&b"bc"[..], "err"));
+        assert_eq!(profile(new(DEFAULT, b"a"), "take", |i| take(i, 3)).into_inner(), State::Incomplete(2));
+        assert_eq!(profile(new(DEFAULT, b"ab"), "token", |i| token(i, b'a')).into_inner(), State::Data(new(DEFAULT, b"b"), b'a'));
+    }
+
+    #[test]
+    fn report_format() {
+        let r = Report(vec![
+            ("a".to_owned(), Stats { calls: 3, data: 1, errors: 1, incomplete: 1, consumed: 4, scanned: 2, time: Duration::from_millis(2) }),
+            ("long_name".to_owned(), Stats::default()),
+        ]);
+
+        assert_eq!(r.get("a").map(|s| s.calls), Some(3));
+        assert_eq!(r.get("b"), None);
+        assert_eq!(format!("{}", r), "\
+rule           calls       data     errors incomplete   consumed    scanned    time (us)
+a                  3          1          1          1          4          2         2000
+long_name          0          0          0          0          0          0            0
+");
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn profile_counters() {
+        reset();
+
+        for &b in &[&b"ab"[..], b"ac", b"b", b"a"] {
+            let _ = profile(new(DEFAULT, b), "ab", |i| profile(i, "a", |i| token(i, b'a')).then(|i| token(i, b'b')));
+        }
+
+        let r = report();
+
+        assert_eq!(r.entries().iter().map(|e| e.0.as_str()).collect::<Vec<_>>(), vec!["a", "ab"]);
+
+        let a  = *r.get("a").unwrap();
+        let ab = *r.get("ab").unwrap();
+
+        assert_eq!((a.calls, a.data, a.errors, a.incomplete, a.consumed, a.scanned), (4, 3, 1, 0, 3, 0));
+        assert_eq!((ab.calls, ab.data, ab.errors, ab.incomplete, ab.consumed, ab.scanned), (4, 1, 2, 1, 2, 1));
+
+        reset();
+
+        assert_eq!(report(), Report::default());
+    }
+
+    #[cfg(not(feature = "profile"))]
+    #[test]
+    fn profile_disabled() {
+        reset();
+
+        let _ = profile(new(DEFAULT, b"abc"), "any", any);
+
+        assert_eq!(report(), Report::default());
+    }
+}
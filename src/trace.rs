@@ -0,0 +1,346 @@
+//! Tracing of parser execution.
+//!
+//! With the `trace` feature enabled the `trace` combinator and every statement of the `parse!`
+//! macro report when they are entered and exited to a `Sink`, together with the offset in the
+//! input, the number of consumed items and the outcome. The default sink prints the events as an
+//! indented tree to stderr:
+//!
+//! ```text
+//! rule @0
+//!   take_while1(is_alpha) @0
+//!   take_while1(is_alpha) @0 => data, consumed 3
+//!   token(b':') @3
+//!   token(b':') @3 => error, consumed 0
+//! rule @0 => error, consumed 3
+//! ```
+//!
+//! Without the `trace` feature `trace` only applies the parser and nothing is reported.
+//!
+//! The sink is set per thread using `set_sink`:
+//!
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//!
+//! use chomp::{parse_only, any};
+//! use chomp::trace::{Event, set_sink, trace};
+//!
+//! let names = Rc::new(RefCell::new(Vec::new()));
+//! let n     = names.clone();
+//!
+//! set_sink(move |e: &Event| if let Event::Enter { name, .. } = *e {
+//!     n.borrow_mut().push(name.to_owned());
+//! });
+//!
+//! assert_eq!(parse_only(|i| trace(i, "any", any), b"a"), Ok(b'a'));
+//!
+//! if cfg!(feature = "trace") {
+//!     assert_eq!(*names.borrow(), vec!["any".to_owned()]);
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+use std::mem;
+
+use {Input, ParseResult};
+
+use primitives::{InputBuffer, InputClone, IntoInner, State};
+
+/// The outcome of a traced parser.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    /// The parser succeeded.
+    Data,
+    /// The parser failed.
+    Error,
+    /// The parser requested the given number of additional items.
+    Incomplete(usize),
+}
+
+/// An event reported to a `Sink`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Event<'e> {
+    /// A traced parser is about to run.
+    Enter {
+        /// Name of the parser.
+        name:   &'e str,
+        /// Number of traced parsers currently running outside of this one.
+        depth:  usize,
+        /// Offset into the input, in items, where the parser starts.
+        offset: usize,
+    },
+    /// A traced parser has completed.
+    Exit {
+        /// Name of the parser.
+        name:     &'e str,
+        /// Number of traced parsers currently running outside of this one.
+        depth:    usize,
+        /// Offset into the input, in items, where the parser started.
+        offset:   usize,
+        /// Number of items consumed by the parser, for errors this is the number of items before
+        /// the position of the error.
+        consumed: usize,
+        /// The outcome of the parser.
+        outcome:  Outcome,
+    },
+}
+
+/// Receiver of trace events.
+pub trait Sink {
+    /// Called for every trace event.
+    ///
+    /// The sink must not run any traced parsers itself.
+    fn event(&mut self, e: &Event);
+}
+
+impl<F> Sink for F
+  where F: FnMut(&Event) {
+    fn event(&mut self, e: &Event) {
+        self(e)
+    }
+}
+
+/// Sink writing events as an indented tree to a `Write` instance.
+#[derive(Debug)]
+pub struct WriteSink<W: Write>(pub W);
+
+impl<W: Write> Sink for WriteSink<W> {
+    fn event(&mut self, e: &Event) {
+        // Tracing is a debugging aid, failing to write should not affect the parser
+        let _ = match *e {
+            Event::Enter { name, depth, offset } =>
+                writeln!(self.0, "{:pad$}{} @{}", "", name, offset, pad = depth * 2),
+            Event::Exit { name, depth, offset, consumed, outcome: Outcome::Data } =>
+                writeln!(self.0, "{:pad$}{} @{} => data, consumed {}", "", name, offset, consumed, pad = depth * 2),
+            Event::Exit { name, depth, offset, consumed, outcome: Outcome::Error } =>
+                writeln!(self.0, "{:pad$}{} @{} => error, consumed {}", "", name, offset, consumed, pad = depth * 2),
+            Event::Exit { name, depth, offset, outcome: Outcome::Incomplete(n), .. } =>
+                writeln!(self.0, "{:pad$}{} @{} => incomplete, requested {}", "", name, offset, n, pad = depth * 2),
+        };
+    }
+}
+
+/// Per-thread trace state.
+struct Tracer {
+    /// The sink, the default sink is used if none has been set.
+    sink:  Option<Box<dyn Sink>>,
+    /// Number of currently running traced parsers.
+    depth: usize,
+    /// Address of the start of the input offsets are relative to.
+    base:  usize,
+    /// Address of the end of the input offsets are relative to.
+    end:   usize,
+}
+
+thread_local!(static TRACER: RefCell<Tracer> = RefCell::new(Tracer {
+    sink:  None,
+    depth: 0,
+    base:  0,
+    end:   0,
+}));
+
+/// Sets the sink receiving the trace events of the current thread, returning the previous sink.
+///
+/// The default sink is a `WriteSink` writing to stderr.
+pub fn set_sink<S>(sink: S) -> Option<Box<dyn Sink>>
+  where S: Sink + 'static {
+    TRACER.with(|t| t.borrow_mut().sink.replace(Box::new(sink)))
+}
+
+/// Removes the sink of the current thread, restoring the default sink.
+pub fn take_sink() -> Option<Box<dyn Sink>> {
+    TRACER.with(|t| t.borrow_mut().sink.take())
+}
+
+fn emit(e: &Event) {
+    TRACER.with(|t| {
+        let mut t = t.borrow_mut();
+
+        match t.sink {
+            Some(ref mut s) => s.event(e),
+            None            => WriteSink(io::stderr()).event(e),
+        }
+    })
+}
+
+/// Offset of ``b`` relative to the input being traced.
+fn offset<I>(base: usize, b: &[I]) -> usize {
+    match mem::size_of::<I>() {
+        0 => 0,
+        n => (b.as_ptr() as usize).wrapping_sub(base) / n,
+    }
+}
+
+/// Applies the parser ``f``, reporting its execution under the given name when the `trace`
+/// feature is enabled.
+///
+/// ```
+/// use chomp::{parse_only, take_while1};
+/// use chomp::trace::trace;
+///
+/// let word = |i| trace(i, "word", |i| take_while1(i, |c| c != b' '));
+///
+/// assert_eq!(parse_only(word, b"abc def"), Ok(&b"abc"[..]));
+/// ```
+#[inline(always)]
+pub fn trace<'a, I, T, E, F>(i: Input<'a, I>, name: &str, f: F) -> ParseResult<'a, I, T, E>
+  where F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    if cfg!(feature = "trace") {
+        traced(i, name, f)
+    } else {
+        f(i)
+    }
+}
+
+/// Implementation of `trace` when the `trace` feature is enabled.
+fn traced<'a, I, T, E, F>(i: Input<'a, I>, name: &str, f: F) -> ParseResult<'a, I, T, E>
+  where F: FnOnce(Input<'a, I>) -> ParseResult<'a, I, T, E> {
+    let buf = i.buffer();
+
+    let (depth, start) = TRACER.with(|t| {
+        let mut t = t.borrow_mut();
+
+        let (start, end) = (buf.as_ptr() as usize, buf.as_ptr() as usize + mem::size_of_val(buf));
+
+        // The remainder of an input shares the end with the original input, anything else is a
+        // new input and offsets will be relative to it
+        if t.depth == 0 && (end != t.end || start < t.base) {
+            t.base = start;
+            t.end  = end;
+        }
+
+        t.depth += 1;
+
+        (t.depth - 1, offset(t.base, buf))
+    });
+
+    emit(&Event::Enter { name, depth, offset: start });
+
+    let (r, consumed, outcome) = match f(i.clone()).into_inner() {
+        State::Data(b, t)    => {
+            let n = buf.len() - b.buffer().len();
+
+            (b.ret(t), n, Outcome::Data)
+        },
+        State::Error(b, e)   => {
+            let n = buf.len().saturating_sub(b.len());
+
+            (i.replace(b).err(e), n, Outcome::Error)
+        },
+        State::Incomplete(n) => (i.incomplete(n), 0, Outcome::Incomplete(n)),
+    };
+
+    TRACER.with(|t| t.borrow_mut().depth -= 1);
+
+    emit(&Event::Exit { name, depth, offset: start, consumed, outcome });
+
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use primitives::State;
+    use primitives::input::{new, DEFAULT, END_OF_INPUT};
+    use primitives::IntoInner;
+    use parsers::{any, take, token};
+
+    use super::*;
+
+    /// Runs ``f`` collecting all the events it reports.
+    fn events<F: FnOnce()>(f: F) -> Vec<String> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let e      = events.clone();
+
+        set_sink(move |ev: &Event| e.borrow_mut().push(format!("{:?}", ev)));
+
+        f();
+
+        take_sink();
+
+        let r = events.borrow().clone();
+
+        r
+    }
+
+    #[test]
+    fn write_sink() {
+        let mut s = WriteSink(Vec::new());
+
+        s.event(&Event::Enter { name: "a", depth: 0, offset: 0 });
+        s.event(&Event::Enter { name: "b", depth: 1, offset: 2 });
+        s.event(&Event::Exit { name: "b", depth: 1, offset: 2, consumed: 1, outcome: Outcome::Error });
+        s.event(&Event::Exit { name: "a", depth: 0, offset: 0, consumed: 0, outcome: Outcome::Incomplete(3) });
+        s.event(&Event::Exit { name: "c", depth: 0, offset: 0, consumed: 4, outcome: Outcome::Data });
+
+        assert_eq!(String::from_utf8(s.0).unwrap(), "a @0
+  b @2
+  b @2 => error, consumed 1
+a @0 => incomplete, requested 3
+c @0 => data, consumed 4
+");
+    }
+
+    #[test]
+    fn trace_result() {
+        assert_eq!(trace(new(DEFAULT, b"abc"), "any", any).into_inner(), State::Data(new(DEFAULT, b"bc"), b'a'));
+        assert_eq!(trace(new(DEFAULT, b"abc"), "err", |i| take(i, 1).map_err(|_| "take").then(|i| i.err::<(), _>("err"))).into_inner(), State::Error(&b"bc"[..], "err"));
+        assert_eq!(trace(new(DEFAULT, b"a"), "take", |i| take(i, 3)).into_inner(), State::Incomplete(2));
+        assert_eq!(trace(new(END_OF_INPUT, b"a"), "token", |i| token(i, b'a')).into_inner(), State::Data(new(END_OF_INPUT, b""), b'a'));
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn trace_events() {
+        let e = events(|| {
+            let _ = trace(new(END_OF_INPUT, b"abc"), "outer", |i| trace(i, "a", |i| token(i, b'a'))
+                .then(|i| trace(i, "c", |i| token(i, b'c'))));
+        });
+
+        assert_eq!(e, vec![
+            format!("{:?}", Event::Enter { name: "outer", depth: 0, offset: 0 }),
+            format!("{:?}", Event::Enter { name: "a", depth: 1, offset: 0 }),
+            format!("{:?}", Event::Exit { name: "a", depth: 1, offset: 0, consumed: 1, outcome: Outcome::Data }),
+            format!("{:?}", Event::Enter { name: "c", depth: 1, offset: 1 }),
+            format!("{:?}", Event::Exit { name: "c", depth: 1, offset: 1, consumed: 0, outcome: Outcome::Error }),
+            format!("{:?}", Event::Exit { name: "outer", depth: 0, offset: 0, consumed: 1, outcome: Outcome::Error }),
+        ]);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn trace_parse_statements() {
+        use Error;
+
+        let e = events(|| {
+            let _ = parse!{new(DEFAULT, b"ab");
+                let a = any();
+                        token(b'b');
+                ret @ _, Error<u8>: a
+            };
+        });
+
+        assert_eq!(e, vec![
+            format!("{:?}", Event::Enter { name: "any()", depth: 0, offset: 0 }),
+            format!("{:?}", Event::Exit { name: "any()", depth: 0, offset: 0, consumed: 1, outcome: Outcome::Data }),
+            format!("{:?}", Event::Enter { name: "token(b'b')", depth: 0, offset: 1 }),
+            format!("{:?}", Event::Exit { name: "token(b'b')", depth: 0, offset: 1, consumed: 1, outcome: Outcome::Data }),
+            format!("{:?}", Event::Enter { name: "ret @ _, Error<u8>: a", depth: 0, offset: 2 }),
+            format!("{:?}", Event::Exit { name: "ret @ _, Error<u8>: a", depth: 0, offset: 2, consumed: 0, outcome: Outcome::Data }),
+        ]);
+    }
+
+    #[cfg(not(feature = "trace"))]
+    #[test]
+    fn trace_disabled() {
+        let e = events(|| {
+            let _ = trace(new(DEFAULT, b"abc"), "any", any);
+        });
+
+        assert!(e.is_empty());
+    }
+}
@@ -0,0 +1,18 @@
+// error-pattern:error: unexpected end of macro invocation
+
+#[macro_use]
+extern crate chomp;
+
+use chomp::{ParseResult, Input, parse_only, any};
+
+// if needs an else since both branches must result in a parser
+fn parser(i: Input<u8>) -> ParseResult<u8, u8, chomp::parsers::Error<u8>> {
+    parse!{i;
+        let c = any();
+        if c == b'a' { any() }
+    }
+}
+
+fn main() {
+    let r = parse_only(parser, b"5");
+}
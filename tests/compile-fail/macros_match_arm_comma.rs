@@ -0,0 +1,25 @@
+// error-pattern:error: no rules expected reserved identifier `_`
+
+#[macro_use]
+extern crate chomp;
+
+use chomp::{ParseResult, Input, parse_only};
+
+// Match-arms which are not blocks must be ended by a comma
+fn parser(i: Input<u8>) -> ParseResult<u8, u8, ()> {
+    fn f(i: Input<u8>) -> ParseResult<u8, u8, ()> {
+        i.ret(3)
+    }
+
+    parse!{i;
+        let x = f();
+        match x {
+            3 => f()
+            _ => f(),
+        }
+    }
+}
+
+fn main() {
+    let r = parse_only(parser, b"5");
+}
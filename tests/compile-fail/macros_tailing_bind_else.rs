@@ -0,0 +1,21 @@
+// error-pattern:error: unexpected end of macro invocation
+
+#[macro_use]
+extern crate chomp;
+
+use chomp::{ParseResult, Input, parse_only};
+
+// The last part of a parse! block must be an expression, also for let-else
+fn parser(i: Input<u8>) -> ParseResult<u8, u8, ()> {
+    fn f(i: Input<u8>) -> ParseResult<u8, Option<u8>, ()> {
+        i.ret(Some(3))
+    }
+
+    parse!{i;
+        let Some(x) = f() else err ()
+    }
+}
+
+fn main() {
+    let r = parse_only(parser, b"5");
+}